@@ -5,6 +5,9 @@ from __future__ import annotations
 from ._textum import (
     PyBoundary as Boundary,
 )
+from ._textum import (
+    PyOperation as Operation,
+)
 from ._textum import (
     PyPatch as Patch,
 )
@@ -20,16 +23,19 @@ from ._textum import (
 from ._textum import (
     load_patches_from_json,
     save_patches_to_json,
+    undo,
 )
 
 __version__ = "0.1.0"
 
 __all__ = [
     "Patch",
+    "Operation",
     "PatchSet",
     "Snippet",
     "Boundary",
     "Target",
     "load_patches_from_json",
     "save_patches_to_json",
+    "undo",
 ]
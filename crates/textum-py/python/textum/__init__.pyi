@@ -9,8 +9,15 @@ class Patch:
         self,
         file: str,
         snippet: Snippet,
-        replacement: str,
+        replacement: str = "",
         symbol_path: Optional[list[str]] = None,
+        operation: Optional[Operation] = None,
+        idempotent: bool = False,
+        reindent: bool = False,
+        id: Optional[str] = None,
+        description: Optional[str] = None,
+        depends_on: Optional[list[str]] = None,
+        priority: int = 0,
     ) -> None: ...
     @staticmethod
     def from_literal_target(
@@ -22,12 +29,45 @@ class Patch:
     ) -> Patch: ...
     def apply_to_string(self, content: str) -> str: ...
 
+class Operation:
+    """What a patch does with the range its snippet resolves to."""
+
+    @staticmethod
+    def replace() -> Operation: ...
+    @staticmethod
+    def insert_before() -> Operation: ...
+    @staticmethod
+    def insert_after() -> Operation: ...
+    @staticmethod
+    def delete() -> Operation: ...
+    @staticmethod
+    def wrap(prefix: str, suffix: str) -> Operation: ...
+    @staticmethod
+    def prepend() -> Operation: ...
+    @staticmethod
+    def append() -> Operation: ...
+    @staticmethod
+    def create_file(overwrite: bool = False) -> Operation: ...
+    @staticmethod
+    def delete_file() -> Operation: ...
+    @staticmethod
+    def rename_file(to: str) -> Operation: ...
+
 class PatchSet:
     """A collection of patches that can be applied together."""
 
-    def __init__(self) -> None: ...
+    def __init__(
+        self,
+        mode: str = "snapshot",
+        overlap: str = "error",
+        backup: Optional[str] = None,
+        journal: str = ".textum",
+    ) -> None: ...
     def add(self, patch: Patch) -> None: ...
     def apply_to_files(self) -> dict[str, str]: ...
+    def apply_to_changes(self) -> dict[str, Optional[str]]: ...
+    def apply_to_map(self, files: dict[str, str]) -> dict[str, str]: ...
+    def write(self) -> None: ...
 
 class Snippet:
     """Specifies a text range through boundary markers or positions."""
@@ -52,7 +92,12 @@ class Target:
     """Defines what text position or pattern a boundary matches."""
 
     @staticmethod
-    def literal(needle: str) -> Target: ...
+    def literal(
+        needle: str,
+        word_boundary: bool = False,
+        case_insensitive: bool = False,
+        normalization: Optional[str] = None,
+    ) -> Target: ...
     @staticmethod
     def line(line_number: int) -> Target: ...
     @staticmethod
@@ -64,3 +109,4 @@ class Target:
 
 def load_patches_from_json(json_str: str) -> list[Patch]: ...
 def save_patches_to_json(patches: list[Patch]) -> str: ...
+def undo(journal: str = ".textum") -> list[str]: ...
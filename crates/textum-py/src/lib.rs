@@ -1,6 +1,9 @@
 use pyo3::prelude::*;
 use std::collections::HashMap;
-use textum::{Boundary, BoundaryMode, Patch, PatchSet, Snippet, Target};
+use textum::{
+    ApplyMode, Backup, Boundary, BoundaryMode, FileChange, Journal, LiteralOptions, Normalization,
+    Operation, OverlapPolicy, Patch, PatchSet, Snippet, Target,
+};
 
 /// A Python wrapper for the Patch struct
 #[pyclass]
@@ -12,22 +15,34 @@ struct PyPatch {
 #[pymethods]
 impl PyPatch {
     #[new]
-    #[pyo3(signature = (file, snippet, replacement, symbol_path=None))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (file, snippet, replacement=String::new(), symbol_path=None, operation=None, idempotent=false, reindent=false, id=None, description=None, depends_on=None, priority=0))]
     fn new(
         file: String,
         snippet: PySnippet,
         replacement: String,
         #[allow(unused_variables)] symbol_path: Option<Vec<String>>,
+        operation: Option<PyOperation>,
+        idempotent: bool,
+        reindent: bool,
+        id: Option<String>,
+        description: Option<String>,
+        depends_on: Option<Vec<String>>,
+        priority: i32,
     ) -> Self {
-        PyPatch {
-            inner: Patch {
-                file,
-                snippet: snippet.inner,
-                replacement,
-                #[cfg(feature = "symbol_path")]
-                symbol_path,
-            },
+        let mut inner = Patch::new(file, snippet.inner, replacement)
+            .with_operation(operation.map(|op| op.inner).unwrap_or_default());
+        inner.idempotent = idempotent;
+        inner.reindent = reindent;
+        inner.id = id;
+        inner.description = description;
+        inner.depends_on = depends_on.unwrap_or_default();
+        inner.priority = priority;
+        #[cfg(feature = "symbol_path")]
+        {
+            inner.symbol_path = symbol_path;
         }
+        PyPatch { inner }
     }
 
     /// Create a patch from a literal string target
@@ -69,12 +84,107 @@ impl PyPatch {
 
     fn __repr__(&self) -> String {
         format!(
-            "Patch(file='{}', replacement='{}')",
-            self.inner.file, self.inner.replacement
+            "Patch(file='{}', replacement='{}', operation={:?})",
+            self.inner.file, self.inner.replacement, self.inner.operation
         )
     }
 }
 
+/// A Python wrapper for Operation
+#[pyclass]
+#[derive(Clone)]
+struct PyOperation {
+    inner: Operation,
+}
+
+#[pymethods]
+impl PyOperation {
+    /// Replace the resolved range with the replacement text
+    #[staticmethod]
+    fn replace() -> Self {
+        PyOperation {
+            inner: Operation::Replace,
+        }
+    }
+
+    /// Insert the replacement text before the resolved range
+    #[staticmethod]
+    fn insert_before() -> Self {
+        PyOperation {
+            inner: Operation::InsertBefore,
+        }
+    }
+
+    /// Insert the replacement text after the resolved range
+    #[staticmethod]
+    fn insert_after() -> Self {
+        PyOperation {
+            inner: Operation::InsertAfter,
+        }
+    }
+
+    /// Remove the resolved range
+    #[staticmethod]
+    fn delete() -> Self {
+        PyOperation {
+            inner: Operation::Delete,
+        }
+    }
+
+    /// Surround the resolved range with a prefix and suffix
+    #[staticmethod]
+    fn wrap(prefix: String, suffix: String) -> Self {
+        PyOperation {
+            inner: Operation::Wrap { prefix, suffix },
+        }
+    }
+
+    /// Insert the replacement text at the start of the file
+    #[staticmethod]
+    fn prepend() -> Self {
+        PyOperation {
+            inner: Operation::Prepend,
+        }
+    }
+
+    /// Insert the replacement text at the end of the file
+    #[staticmethod]
+    fn append() -> Self {
+        PyOperation {
+            inner: Operation::Append,
+        }
+    }
+
+    /// Create the file with the replacement text as its contents
+    #[staticmethod]
+    #[pyo3(signature = (overwrite=false))]
+    fn create_file(overwrite: bool) -> Self {
+        PyOperation {
+            inner: Operation::CreateFile { overwrite },
+        }
+    }
+
+    /// Delete the file
+    #[staticmethod]
+    fn delete_file() -> Self {
+        PyOperation {
+            inner: Operation::DeleteFile,
+        }
+    }
+
+    /// Move the file to a new path
+    #[staticmethod]
+    fn rename_file(to: String) -> Self {
+        PyOperation {
+            inner: Operation::RenameFile { to },
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Operation({:?})", self.inner)
+    }
+}
+
 /// A Python wrapper for PatchSet
 #[pyclass]
 struct PyPatchSet {
@@ -84,10 +194,15 @@ struct PyPatchSet {
 #[pymethods]
 impl PyPatchSet {
     #[new]
-    fn new() -> Self {
-        PyPatchSet {
-            inner: PatchSet::new(),
+    #[pyo3(signature = (mode="snapshot", overlap="error", backup=None, journal=".textum"))]
+    fn new(mode: &str, overlap: &str, backup: Option<&str>, journal: &str) -> PyResult<Self> {
+        let mut inner = PatchSet::new()
+            .with_mode(parse_apply_mode(mode)?)
+            .with_overlap_policy(parse_overlap_policy(overlap)?);
+        if let Some(backup) = backup {
+            inner = inner.with_backup(parse_backup(backup, journal)?);
         }
+        Ok(PyPatchSet { inner })
     }
 
     /// Add a patch to this set
@@ -102,6 +217,45 @@ impl PyPatchSet {
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
     }
 
+    /// Apply all patches, mapping each changed file to its new contents (None if deleted)
+    fn apply_to_changes(&self) -> PyResult<HashMap<String, Option<String>>> {
+        let changes = self
+            .inner
+            .apply_to_changes()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))?;
+        Ok(changes
+            .into_iter()
+            .map(|(file, change)| match change {
+                FileChange::Write(content) => (file, Some(content)),
+                FileChange::Delete => (file, None),
+            })
+            .collect())
+    }
+
+    /// Apply all patches to contents held in memory, returning every file after the patches
+    fn apply_to_map(&self, files: HashMap<String, String>) -> PyResult<HashMap<String, String>> {
+        let ropes = files
+            .into_iter()
+            .map(|(file, content)| (file, ropey::Rope::from_str(&content)))
+            .collect();
+        let patched = self
+            .inner
+            .apply_to_map(ropes)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))?;
+        Ok(patched
+            .into_iter()
+            .map(|(file, rope)| (file, rope.to_string()))
+            .collect())
+    }
+
+    /// Apply all patches and write the changes to disk, restoring every file if any write fails
+    fn write(&self) -> PyResult<()> {
+        self.inner
+            .write()
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e)))
+    }
+
     fn __repr__(&self) -> String {
         "PatchSet()".to_string()
     }
@@ -187,12 +341,30 @@ struct PyTarget {
 
 #[pymethods]
 impl PyTarget {
-    /// Create a Literal target
+    /// Create a Literal target, optionally matching whole words only, ignoring case, or
+    /// comparing Unicode-normalized text ('nfc' or 'nfkc')
     #[staticmethod]
-    fn literal(needle: String) -> Self {
-        PyTarget {
-            inner: Target::Literal(needle),
-        }
+    #[pyo3(signature = (needle, word_boundary=false, case_insensitive=false, normalization=None))]
+    fn literal(
+        needle: String,
+        word_boundary: bool,
+        case_insensitive: bool,
+        normalization: Option<String>,
+    ) -> PyResult<Self> {
+        let normalization = normalization
+            .map(|form| parse_normalization(&form))
+            .transpose()?;
+        let options = LiteralOptions {
+            word_boundary,
+            case_insensitive,
+            normalization,
+        };
+        let inner = if options == LiteralOptions::default() {
+            Target::Literal(needle)
+        } else {
+            Target::literal_with(needle, options)
+        };
+        Ok(PyTarget { inner })
     }
 
     /// Create a Line target
@@ -248,6 +420,26 @@ fn save_patches_to_json(patches: Vec<Bound<'_, PyPatch>>) -> PyResult<String> {
     Ok(facet_json::to_string(&inner_patches))
 }
 
+/// Restore the files changed by the last run in a journal, returning their paths
+#[pyfunction]
+#[pyo3(signature = (journal=".textum"))]
+fn undo(journal: &str) -> PyResult<Vec<String>> {
+    let to_err =
+        |e: textum::PatchError| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}", e));
+    let run = Journal::new(journal)
+        .last()
+        .map_err(to_err)?
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "No runs to undo in {}",
+                journal
+            ))
+        })?;
+    let files = run.files.iter().map(|entry| entry.file.clone()).collect();
+    run.restore().map_err(to_err)?;
+    Ok(files)
+}
+
 // Helper function to parse boundary mode strings
 fn parse_boundary_mode(mode: &str) -> PyResult<BoundaryMode> {
     match mode.to_lowercase().as_str() {
@@ -260,14 +452,66 @@ fn parse_boundary_mode(mode: &str) -> PyResult<BoundaryMode> {
     }
 }
 
+// Helper function to parse patch set apply mode strings
+fn parse_apply_mode(mode: &str) -> PyResult<ApplyMode> {
+    match mode.to_lowercase().as_str() {
+        "snapshot" => Ok(ApplyMode::Snapshot),
+        "sequential" => Ok(ApplyMode::Sequential),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid apply mode: '{}'. Must be 'snapshot' or 'sequential'",
+            mode
+        ))),
+    }
+}
+
+// Helper function to parse patch set overlap policy strings
+fn parse_overlap_policy(policy: &str) -> PyResult<OverlapPolicy> {
+    match policy.to_lowercase().as_str() {
+        "error" => Ok(OverlapPolicy::Error),
+        "merge" => Ok(OverlapPolicy::Merge),
+        "first-wins" => Ok(OverlapPolicy::FirstWins),
+        "last-wins" => Ok(OverlapPolicy::LastWins),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid overlap policy: '{}'. Must be 'error', 'merge', 'first-wins' or 'last-wins'",
+            policy
+        ))),
+    }
+}
+
+// Helper function to parse backup mode strings
+fn parse_backup(mode: &str, journal: &str) -> PyResult<Backup> {
+    match mode.to_lowercase().as_str() {
+        "orig" => Ok(Backup::Suffix),
+        "journal" => Ok(Backup::Journal(journal.into())),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid backup mode: '{}'. Must be 'orig' or 'journal'",
+            mode
+        ))),
+    }
+}
+
+// Helper function to parse normalization form strings
+fn parse_normalization(form: &str) -> PyResult<Normalization> {
+    match form.to_lowercase().as_str() {
+        "nfc" => Ok(Normalization::Nfc),
+        "nfkc" => Ok(Normalization::Nfkc),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid normalization form: '{}'. Must be 'nfc' or 'nfkc'",
+            form
+        ))),
+    }
+}
+
 #[pymodule]
 fn _textum(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyPatch>()?;
+    m.add_class::<PyOperation>()?;
     m.add_class::<PyPatchSet>()?;
     m.add_class::<PySnippet>()?;
     m.add_class::<PyBoundary>()?;
     m.add_class::<PyTarget>()?;
     m.add_function(wrap_pyfunction!(load_patches_from_json, m)?)?;
     m.add_function(wrap_pyfunction!(save_patches_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(undo, m)?)?;
     Ok(())
 }
@@ -39,3 +39,214 @@ def test_line_range():
 
     result = patch.apply_to_string(content)
     assert result == "line1\nreplaced\nline4\n"
+
+
+def test_insert_before_operation():
+    """Test inserting text ahead of a match."""
+    boundary = textum.Boundary(textum.Target.literal("world"), "include")
+    patch = textum.Patch(
+        file="test.txt",
+        snippet=textum.Snippet.at(boundary),
+        replacement="big ",
+        operation=textum.Operation.insert_before(),
+    )
+
+    assert patch.apply_to_string("hello world") == "hello big world"
+
+
+def test_wrap_operation():
+    """Test wrapping a match in a prefix and suffix."""
+    boundary = textum.Boundary(textum.Target.literal("world"), "include")
+    patch = textum.Patch(
+        file="test.txt",
+        snippet=textum.Snippet.at(boundary),
+        operation=textum.Operation.wrap("[", "]"),
+    )
+
+    assert patch.apply_to_string("hello world") == "hello [world]"
+
+
+def test_append_operation():
+    """Test appending to the end of a file."""
+    patch = textum.Patch(
+        file="test.txt",
+        snippet=textum.Snippet.all(),
+        replacement="tail\n",
+        operation=textum.Operation.append(),
+    )
+
+    assert patch.apply_to_string("head\n") == "head\ntail\n"
+
+
+def test_idempotent_patch():
+    """Test that an idempotent insertion is not repeated."""
+    boundary = textum.Boundary(textum.Target.literal("fn main"), "include")
+    patch = textum.Patch(
+        file="test.rs",
+        snippet=textum.Snippet.at(boundary),
+        replacement="// entry\n",
+        operation=textum.Operation.insert_before(),
+        idempotent=True,
+    )
+
+    once = patch.apply_to_string("fn main() {}\n")
+    assert patch.apply_to_string(once) == once
+
+
+def test_reindent_patch():
+    """Test that a replacement is re-indented to the patched line."""
+    boundary = textum.Boundary(textum.Target.literal("pass"), "include")
+    patch = textum.Patch(
+        file="test.py",
+        snippet=textum.Snippet.at(boundary),
+        replacement="if x:\n    return 1",
+        reindent=True,
+    )
+
+    result = patch.apply_to_string("def f():\n    pass\n")
+    assert result == "def f():\n    if x:\n        return 1\n"
+
+
+def test_file_operations(tmp_path):
+    """Test creating, deleting and renaming files in one patch set."""
+    deleted = tmp_path / "deleted.txt"
+    deleted.write_text("gone\n")
+    source = tmp_path / "from.txt"
+    source.write_text("moved\n")
+    created = str(tmp_path / "created.txt")
+    dest = str(tmp_path / "to.txt")
+
+    patchset = textum.PatchSet()
+    for file, operation, replacement in [
+        (created, textum.Operation.create_file(), "new\n"),
+        (str(deleted), textum.Operation.delete_file(), ""),
+        (str(source), textum.Operation.rename_file(dest), ""),
+    ]:
+        patchset.add(
+            textum.Patch(
+                file=file,
+                snippet=textum.Snippet.all(),
+                replacement=replacement,
+                operation=operation,
+            )
+        )
+
+    assert patchset.apply_to_changes() == {
+        created: "new\n",
+        str(deleted): None,
+        str(source): None,
+        dest: "moved\n",
+    }
+
+
+def test_patch_identity(tmp_path):
+    """Test that errors cite the failing patch's id."""
+    path = tmp_path / "ids.txt"
+    path.write_text("hello\n")
+    boundary = textum.Boundary(textum.Target.literal("goodbye"), "include")
+
+    patchset = textum.PatchSet()
+    patchset.add(
+        textum.Patch(
+            file=str(path),
+            snippet=textum.Snippet.at(boundary),
+            replacement="hi",
+            id="greet",
+            description="Shorten the greeting",
+        )
+    )
+
+    try:
+        patchset.apply_to_files()
+    except RuntimeError as err:
+        assert '"greet"' in str(err)
+    else:
+        raise AssertionError("expected the patch to fail")
+
+
+def test_sequential_patchset(tmp_path):
+    """Test that sequential patches see earlier patches' changes."""
+    path = tmp_path / "seq.txt"
+    path.write_text("old\n")
+
+    patchset = textum.PatchSet(mode="sequential")
+    for needle, replacement in [("old", "new"), ("new", "newer")]:
+        patchset.add(
+            textum.Patch.from_literal_target(str(path), needle, "include", replacement)
+        )
+
+    assert patchset.apply_to_files() == {str(path): "newer\n"}
+
+
+def test_overlap_policy(tmp_path):
+    """Test that the overlap policy decides which overlapping patch applies."""
+    path = tmp_path / "overlap.txt"
+    path.write_text("abcdef")
+
+    patchset = textum.PatchSet(overlap="first-wins")
+    for needle, replacement in [("bcd", "XXX"), ("def", "YYY")]:
+        patchset.add(
+            textum.Patch.from_literal_target(str(path), needle, "include", replacement)
+        )
+
+    assert patchset.apply_to_files() == {str(path): "aXXXef"}
+
+
+def test_insertion_priority(tmp_path):
+    """Test that insertions at one position follow priority, then declaration order."""
+    path = tmp_path / "insert.txt"
+    path.write_text("xy")
+
+    patchset = textum.PatchSet()
+    for text, priority in [("a", 0), ("b", 1), ("c", 0)]:
+        boundary = textum.Boundary(textum.Target.char(1), "include")
+        patchset.add(
+            textum.Patch(
+                str(path),
+                textum.Snippet.at(boundary),
+                text,
+                operation=textum.Operation.insert_before(),
+                priority=priority,
+            )
+        )
+
+    assert patchset.apply_to_files() == {str(path): "xbacy"}
+
+
+def test_write(tmp_path):
+    """Test that writing a patch set changes the files on disk."""
+    path = tmp_path / "write.txt"
+    path.write_text("hello world\n")
+
+    patchset = textum.PatchSet()
+    patchset.add(textum.Patch.from_literal_target(str(path), "world", "include", "rust"))
+    patchset.write()
+
+    assert path.read_text() == "hello rust\n"
+
+
+def test_journal_undo(tmp_path):
+    """Test that a journaled write can be undone."""
+    path = tmp_path / "undo.txt"
+    path.write_text("hello world\n")
+    journal = str(tmp_path / "journal")
+
+    patchset = textum.PatchSet(backup="journal", journal=journal)
+    patchset.add(textum.Patch.from_literal_target(str(path), "world", "include", "rust"))
+    patchset.write()
+    assert path.read_text() == "hello rust\n"
+
+    assert textum.undo(journal) == [str(path)]
+    assert path.read_text() == "hello world\n"
+
+
+def test_apply_to_map():
+    """Test that patches apply to in-memory contents without touching disk."""
+    patchset = textum.PatchSet()
+    patchset.add(textum.Patch.from_literal_target("a.txt", "world", "include", "rust"))
+
+    files = {"a.txt": "hello world\n", "b.txt": "untouched\n"}
+    assert patchset.apply_to_map(files) == {
+        "a.txt": "hello rust\n",
+        "b.txt": "untouched\n",
+    }
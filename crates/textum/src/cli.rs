@@ -7,8 +7,12 @@
 //! patch formats, textum operates at character granularity and can compose multiple patches
 //! with automatic offset tracking.
 //!
-//! Reads a JSON array of patches from a file or stdin and applies them to their target files.
-//! Modified files are written back to disk unless `--dry-run` is specified.
+//! Reads a JSON array of patches (or an object holding `patches` alongside `mode` and
+//! `line_ending` settings) from a file or stdin and applies them to their target files.
+//! Modified files are written back to disk unless `--dry-run` is specified. With `--inverse`,
+//! the patches undoing the run are saved as JSON, and applying that file reverts the change.
+//! With `--backup journal`, the original files are kept in a journal and `textum undo` restores
+//! the last run.
 #![allow(clippy::multiple_crate_versions)]
 
 /// Command-line interface for applying patches from JSON.
@@ -17,11 +21,15 @@ pub mod inner {
     use facet::Facet;
     use std::fs;
     use std::io::{self, Read};
-    use textum::{Patch, PatchSet};
+    use textum::{
+        ApplyMode, Atomicity, Backup, FileChange, Journal, LineEnding, Outcome, OverlapPolicy,
+        PatchSet,
+    };
 
     #[derive(Facet)]
+    #[allow(clippy::struct_excessive_bools)]
     struct Args {
-        /// Path to JSON file containing patches (reads from stdin if not provided)
+        /// Path to JSON file containing patches (reads from stdin if not provided), or `undo`
         #[facet(positional, default)]
         patch_file: Option<String>,
 
@@ -33,6 +41,38 @@ pub mod inner {
         #[facet(named, short = 'v')]
         verbose: bool,
 
+        /// Write the patches that undo this run to the given JSON file
+        #[facet(named, default)]
+        inverse: Option<String>,
+
+        /// Apply each file's patches one at a time, each seeing the previous one's changes
+        #[facet(named)]
+        sequential: bool,
+
+        /// Line ending for inserted text: preserve (default), lf, crlf or verbatim
+        #[facet(named, default)]
+        line_endings: Option<String>,
+
+        /// What to do when patches overlap: error (default), merge, first-wins or last-wins
+        #[facet(named, default)]
+        overlap: Option<String>,
+
+        /// What a failing patch aborts: set (default), file or patch
+        #[facet(named, default)]
+        atomicity: Option<String>,
+
+        /// Print a JSON report of what each patch did to stdout
+        #[facet(named)]
+        report: bool,
+
+        /// Keep the original of each file written: orig (beside it) or journal (undoable)
+        #[facet(named, default)]
+        backup: Option<String>,
+
+        /// Directory of the journal kept by `--backup journal` and read by `undo`
+        #[facet(named, default)]
+        journal: Option<String>,
+
         /// Show this help message
         #[facet(named, short = 'h')]
         help: bool,
@@ -40,15 +80,50 @@ pub mod inner {
 
     fn print_usage() {
         println!("Usage: textum [OPTIONS] [PATCH_FILE]");
+        println!("       textum undo [--journal <DIR>] [--dry-run]");
         println!();
         println!("Apply syntactic patches to source files with char-level granularity.");
         println!();
         println!("Arguments:");
         println!("  [PATCH_FILE]  Path to JSON file containing patches (reads from stdin if not provided)");
         println!();
+        println!("Commands:");
+        println!(
+            "  undo          Restore the files changed by the last run kept with --backup journal,"
+        );
+        println!("                refusing if any was modified since (use ./undo for a patch file so named)");
+        println!();
         println!("Options:");
         println!("  -n, --dry-run  Preview changes without writing to disk");
         println!("  -v, --verbose  Show verbose output");
+        println!("      --inverse <FILE>");
+        println!("                 Write the patches that undo this run to FILE");
+        println!("                 (apply FILE with textum to revert)");
+        println!("      --sequential");
+        println!("                 Apply each file's patches one at a time in order, each seeing");
+        println!("                 the previous one's changes (default: all see the original)");
+        println!("      --line-endings <MODE>");
+        println!("                 Line ending for inserted text: preserve (the file's dominant");
+        println!("                 line ending, default), lf, crlf or verbatim (as written)");
+        println!("      --overlap <POLICY>");
+        println!("                 What to do when patches overlap: error (default), merge");
+        println!("                 (apply identical edits once and join adjacent ones),");
+        println!("                 first-wins or last-wins");
+        println!("      --atomicity <UNIT>");
+        println!(
+            "                 What a failing patch aborts: set (default, nothing is written),"
+        );
+        println!(
+            "                 file (its file is left unchanged) or patch (only it is left out)"
+        );
+        println!("      --report   Print a JSON report of what each patch did (alone on stdout)");
+        println!("      --backup <MODE>");
+        println!("                 Keep the original of each file written: orig (as FILE.orig)");
+        println!("                 or journal (recorded per run, restored by textum undo)");
+        println!("      --journal <DIR>");
+        println!(
+            "                 Journal directory for --backup journal and undo (default: .textum)"
+        );
         println!("  -h, --help     Show this help message");
     }
 
@@ -75,46 +150,99 @@ pub mod inner {
             std::process::exit(0);
         }
 
+        if args.patch_file.as_deref() == Some("undo") {
+            undo(&args);
+            return Ok(());
+        }
+
         // Read input from file or stdin
-        let input = if let Some(path) = args.patch_file {
-            fs::read_to_string(&path)?
+        let input = if let Some(path) = &args.patch_file {
+            fs::read_to_string(path)?
         } else {
             let mut buf = String::new();
             io::stdin().read_to_string(&mut buf)?;
             buf
         };
 
-        // Parse patches from JSON using facet
-        let patches: Vec<Patch> = facet_json::from_str(&input)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")))?;
+        // Parse patches from JSON, either an array or an object with settings
+        let mut set = PatchSet::from_json(&input)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
 
         if args.verbose {
-            eprintln!("Loaded {} patch(es)", patches.len());
+            eprintln!("Loaded {} patch(es)", set.patches().len());
         }
 
-        let mut set = PatchSet::new();
-        for patch in patches {
-            set.add(patch);
+        if args.sequential {
+            set = set.with_mode(ApplyMode::Sequential);
+        }
+        if let Some(mode) = &args.line_endings {
+            set = set.with_line_ending(parse_line_ending(mode)?);
+        }
+        if let Some(policy) = &args.overlap {
+            set = set.with_overlap_policy(parse_overlap_policy(policy)?);
+        }
+        if let Some(unit) = &args.atomicity {
+            set = set.with_atomicity(parse_atomicity(unit)?);
+        }
+        if let Some(mode) = &args.backup {
+            set = set.with_backup(parse_backup(mode, journal_dir(&args))?);
         }
 
-        // Apply patches
-        match set.apply_to_files() {
-            Ok(results) => {
-                for (file, content) in results {
-                    if args.dry_run {
-                        eprintln!("Would patch: {file}");
-                        if args.verbose {
-                            println!("=== {file} ===\n{content}");
+        // Apply patches, writing them to disk all-or-nothing unless this is a dry run
+        let result = if args.dry_run {
+            set.apply()
+        } else {
+            set.write()
+        };
+        match result {
+            Ok(Outcome {
+                changes,
+                inverse,
+                report,
+                failures,
+                ..
+            }) => {
+                for (file, change) in changes {
+                    match change {
+                        FileChange::Write(content) if args.dry_run => {
+                            eprintln!("Would patch: {file}");
+                            // Keep stdout for the JSON report when one is asked for
+                            if args.verbose && args.report {
+                                eprintln!("=== {file} ===\n{content}");
+                            } else if args.verbose {
+                                println!("=== {file} ===\n{content}");
+                            }
                         }
+                        FileChange::Write(_) => eprintln!("Patched: {file}"),
+                        FileChange::Delete if args.dry_run => eprintln!("Would delete: {file}"),
+                        FileChange::Delete => eprintln!("Deleted: {file}"),
+                    }
+                }
+
+                if let Some(path) = &args.inverse {
+                    if args.dry_run {
+                        eprintln!("Would write inverse: {path}");
                     } else {
-                        fs::write(&file, content)?;
-                        eprintln!("Patched: {file}");
+                        fs::write(path, inverse.to_json())?;
+                        eprintln!("Wrote inverse: {path}");
                     }
                 }
 
+                if args.report {
+                    println!("{}", report.to_json());
+                }
+
                 if args.dry_run && !args.verbose {
                     eprintln!("Dry run complete. Use -v to see changes.");
                 }
+
+                if !failures.is_empty() {
+                    for e in &failures {
+                        eprintln!("Error: {e}");
+                    }
+                    eprintln!("{} patch(es) failed", failures.len());
+                    std::process::exit(1);
+                }
             }
             Err(e) => {
                 eprintln!("Error: {e}");
@@ -124,6 +252,104 @@ pub mod inner {
 
         Ok(())
     }
+
+    /// Restore the files changed by the last run in the journal, exiting on failure.
+    fn undo(args: &Args) {
+        let dir = journal_dir(args);
+        let run = match Journal::new(dir).last() {
+            Ok(Some(run)) => run,
+            Ok(None) => {
+                eprintln!("Error: No runs to undo in {dir}");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        };
+        let files: Vec<String> = run.files.iter().map(|entry| entry.file.clone()).collect();
+        let result = if args.dry_run {
+            run.check()
+        } else {
+            run.restore()
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        let verb = if args.dry_run {
+            "Would restore"
+        } else {
+            "Restored"
+        };
+        for file in files {
+            eprintln!("{verb}: {file}");
+        }
+    }
+
+    /// Returns the journal directory given by `--journal`, or the default.
+    fn journal_dir(args: &Args) -> &str {
+        args.journal.as_deref().unwrap_or(Journal::DEFAULT_DIR)
+    }
+
+    /// Parse the `--backup` option, journaling to `journal`.
+    fn parse_backup(mode: &str, journal: &str) -> io::Result<Backup> {
+        match mode.to_lowercase().as_str() {
+            "orig" => Ok(Backup::Suffix),
+            "journal" => Ok(Backup::Journal(journal.into())),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid backup mode: '{other}'. Must be 'orig' or 'journal'"),
+            )),
+        }
+    }
+
+    /// Parse the `--line-endings` option.
+    fn parse_line_ending(mode: &str) -> io::Result<LineEnding> {
+        match mode.to_lowercase().as_str() {
+            "preserve" => Ok(LineEnding::Preserve),
+            "lf" => Ok(LineEnding::Lf),
+            "crlf" => Ok(LineEnding::Crlf),
+            "verbatim" => Ok(LineEnding::Verbatim),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid line ending: '{other}'. \
+                     Must be 'preserve', 'lf', 'crlf' or 'verbatim'"
+                ),
+            )),
+        }
+    }
+
+    /// Parse the `--atomicity` option.
+    fn parse_atomicity(unit: &str) -> io::Result<Atomicity> {
+        match unit.to_lowercase().as_str() {
+            "set" => Ok(Atomicity::Set),
+            "file" => Ok(Atomicity::File),
+            "patch" => Ok(Atomicity::Patch),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid atomicity: '{other}'. Must be 'set', 'file' or 'patch'"),
+            )),
+        }
+    }
+
+    /// Parse the `--overlap` option.
+    fn parse_overlap_policy(policy: &str) -> io::Result<OverlapPolicy> {
+        match policy.to_lowercase().as_str() {
+            "error" => Ok(OverlapPolicy::Error),
+            "merge" => Ok(OverlapPolicy::Merge),
+            "first-wins" => Ok(OverlapPolicy::FirstWins),
+            "last-wins" => Ok(OverlapPolicy::LastWins),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid overlap policy: '{other}'. \
+                     Must be 'error', 'merge', 'first-wins' or 'last-wins'"
+                ),
+            )),
+        }
+    }
 }
 
 /// Hint replacement CLI for when the cli module is used without building the cli feature.
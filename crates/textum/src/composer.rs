@@ -1,18 +1,58 @@
 //! Composition and application of multiple patches.
 //!
 //! The `PatchSet` type allows you to group multiple patches and apply them together,
-//! with automatic handling of offset adjustments. Patches are grouped by file and
-//! applied in reverse order to maintain stable positions.
+//! with automatic handling of offset adjustments. Patches are grouped by file, every patch
+//! is resolved against the file's text, and the file is rebuilt in a single pass that
+//! applies the edits in ascending order of position.
 
-use crate::patch::{Patch, PatchError};
-use ropey::Rope;
-use std::collections::HashMap;
+#[cfg(feature = "json")]
+use facet::Facet;
+
+use crate::patch::{Edit, Operation, Patch, PatchError, PatchStatus};
+use crate::snip::span::Span;
+use ropey::{Rope, RopeBuilder};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+mod atomicity;
+mod backup;
+mod dependencies;
+mod file_system;
+mod files;
+mod inverse;
+pub mod line_ending;
+mod mode;
+mod overlap;
+mod report;
+mod stamp;
+mod transaction;
+
+pub use atomicity::Atomicity;
+use atomicity::Run;
+pub use backup::{Backup, Journal, JournalEntry, JournalRun};
+pub use file_system::{FileSystem, MemoryFileSystem, StdFileSystem};
+pub use files::FileChange;
+use files::{FileTable, Originals};
+use inverse::InverseLog;
+pub use line_ending::LineEnding;
+pub use mode::ApplyMode;
+pub use overlap::OverlapPolicy;
+use overlap::Resolved;
+pub use report::{ApplyReport, Outcome, PatchReport};
+pub use stamp::FileStamp;
 
 /// A collection of patches that can be applied together.
 ///
-/// `PatchSet` handles the complexity of applying multiple patches to the same file
-/// by sorting them appropriately and tracking offset changes. Patches are applied
-/// in reverse order (highest position first) to avoid invalidating subsequent patches.
+/// `PatchSet` handles the complexity of applying multiple patches to the same file. Each
+/// patch is resolved against the file once, the resolved edits are sorted by position and
+/// checked for overlaps, and the file is rebuilt from them in a single pass.
+///
+/// When several patches insert text at the same position, their text appears in order of
+/// [`Patch::priority`], highest first, then in the order the patches were added. Insertions at
+/// the start of a range another patch replaces come before its replacement text.
 ///
 /// # Examples
 ///
@@ -38,9 +78,22 @@ use std::collections::HashMap;
 /// let results = set.apply_to_files().unwrap();
 /// assert_eq!(results.get("tests/fixtures/sample.txt").unwrap(), "goodbye rust\n");
 /// ```
+#[derive(Debug)]
 pub struct PatchSet {
     /// The patches in this set.
     patches: Vec<Patch>,
+    /// The line ending replacement text is normalised to.
+    line_ending: LineEnding,
+    /// Whether patches to the same file see each other's changes.
+    mode: ApplyMode,
+    /// How overlapping edits are handled.
+    overlap_policy: OverlapPolicy,
+    /// How much of the set a failing patch takes down with it.
+    atomicity: Atomicity,
+    /// Where writing the set keeps the original contents of the files it changes.
+    backup: Option<Backup>,
+    /// Where files are read from and written to.
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl PatchSet {
@@ -57,9 +110,223 @@ impl PatchSet {
     pub fn new() -> Self {
         Self {
             patches: Vec::new(),
+            line_ending: LineEnding::default(),
+            mode: ApplyMode::default(),
+            overlap_policy: OverlapPolicy::default(),
+            atomicity: Atomicity::default(),
+            backup: None,
+            file_system: Arc::new(StdFileSystem),
         }
     }
 
+    /// Set the line ending that replacement text is normalised to.
+    ///
+    /// By default each file's dominant line ending is detected and every `\n` or `\r\n` in
+    /// the text a patch inserts is rewritten to match it, so patches written with `\n` do not
+    /// introduce mixed line endings into CRLF files. Text already in the file is never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, LineEnding, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "a\r\nb\r\n").unwrap();
+    ///
+    /// let patch = Patch::from_literal_target(path.clone(), "b", BoundaryMode::Include, "b\nc");
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(patch.clone());
+    /// assert_eq!(set.apply_to_files().unwrap()[&path], "a\r\nb\r\nc\r\n");
+    ///
+    /// let mut set = PatchSet::new().with_line_ending(LineEnding::Lf);
+    /// set.add(patch);
+    /// assert_eq!(set.apply_to_files().unwrap()[&path], "a\r\nb\nc\r\n");
+    /// ```
+    #[must_use]
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Returns the line ending replacement text is normalised to.
+    #[must_use]
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Set whether patches to the same file see each other's changes.
+    ///
+    /// By default every patch for a file is resolved against the file as it was read
+    /// ([`ApplyMode::Snapshot`]). In [`ApplyMode::Sequential`] mode each patch is resolved and
+    /// applied in turn, so it can target text an earlier patch introduced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{ApplyMode, BoundaryMode, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.rs").to_string_lossy().to_string();
+    /// std::fs::write(&path, "foo();\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new().with_mode(ApplyMode::Sequential);
+    /// set.add(Patch::from_literal_target(path.clone(), "foo", BoundaryMode::Include, "bar"));
+    /// set.add(Patch::from_literal_target(path.clone(), "bar()", BoundaryMode::Include, "bar(1)"));
+    ///
+    /// assert_eq!(set.apply_to_files().unwrap()[&path], "bar(1);\n");
+    /// ```
+    #[must_use]
+    pub fn with_mode(mut self, mode: ApplyMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns whether patches to the same file see each other's changes.
+    #[must_use]
+    pub fn mode(&self) -> ApplyMode {
+        self.mode
+    }
+
+    /// Set how patches whose edits overlap are handled.
+    ///
+    /// By default overlapping edits are an error ([`OverlapPolicy::Error`]), except that
+    /// overlapping deletions are always combined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, OverlapPolicy, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "abcdef").unwrap();
+    ///
+    /// let mut set = PatchSet::new().with_overlap_policy(OverlapPolicy::LastWins);
+    /// set.add(Patch::from_literal_target(path.clone(), "bcd", BoundaryMode::Include, "X"));
+    /// set.add(Patch::from_literal_target(path.clone(), "def", BoundaryMode::Include, "Y"));
+    ///
+    /// assert_eq!(set.apply_to_files().unwrap()[&path], "abcY");
+    /// ```
+    #[must_use]
+    pub fn with_overlap_policy(mut self, overlap_policy: OverlapPolicy) -> Self {
+        self.overlap_policy = overlap_policy;
+        self
+    }
+
+    /// Returns how patches whose edits overlap are handled.
+    #[must_use]
+    pub fn overlap_policy(&self) -> OverlapPolicy {
+        self.overlap_policy
+    }
+
+    /// Set how much of the set a failing patch takes down with it.
+    ///
+    /// By default any failure aborts the whole set ([`Atomicity::Set`]). With
+    /// [`Atomicity::File`] only the failing patch's file is left unchanged, and with
+    /// [`Atomicity::Patch`] only the failing patch is left out. The failures are collected in
+    /// the [`Outcome`] returned by [`PatchSet::apply`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{Atomicity, BoundaryMode, Patch, PatchSet, PatchStatus};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.rs").to_string_lossy().to_string();
+    /// std::fs::write(&path, "foo();\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new().with_atomicity(Atomicity::Patch);
+    /// set.add(Patch::from_literal_target(path.clone(), "foo", BoundaryMode::Include, "bar"));
+    /// set.add(Patch::from_literal_target(path.clone(), "baz", BoundaryMode::Include, "qux"));
+    ///
+    /// let outcome = set.apply().unwrap();
+    /// assert_eq!(outcome.report.patches[1].status, PatchStatus::Failed);
+    /// assert_eq!(outcome.failures.len(), 1);
+    /// assert_eq!(set.apply_to_files().unwrap()[&path], "bar();\n");
+    /// ```
+    #[must_use]
+    pub fn with_atomicity(mut self, atomicity: Atomicity) -> Self {
+        self.atomicity = atomicity;
+        self
+    }
+
+    /// Returns how much of the set a failing patch takes down with it.
+    #[must_use]
+    pub fn atomicity(&self) -> Atomicity {
+        self.atomicity
+    }
+
+    /// Set where [`PatchSet::write`] keeps the original contents of the files it changes.
+    ///
+    /// By default no backups are kept. Unlike the other settings, backups are not part of the
+    /// set's JSON form, since they concern where it is written rather than what it changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{Backup, BoundaryMode, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "old\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new().with_backup(Backup::Suffix);
+    /// set.add(Patch::from_literal_target(path.clone(), "old", BoundaryMode::Include, "new"));
+    /// set.write().unwrap();
+    ///
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+    /// assert_eq!(std::fs::read_to_string(format!("{path}.orig")).unwrap(), "old\n");
+    /// ```
+    #[must_use]
+    pub fn with_backup(mut self, backup: Backup) -> Self {
+        self.backup = Some(backup);
+        self
+    }
+
+    /// Returns where [`PatchSet::write`] keeps the original contents of the files it changes.
+    #[must_use]
+    pub fn backup(&self) -> Option<&Backup> {
+        self.backup.as_ref()
+    }
+
+    /// Set where files are read from and written to.
+    ///
+    /// By default files are on the local disk ([`StdFileSystem`]). Any [`FileSystem`] can be
+    /// given instead, such as a [`MemoryFileSystem`] holding unsaved buffers, and every method
+    /// that reads or writes files uses it, including for the backups of a [`Backup`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use textum::{BoundaryMode, MemoryFileSystem, Patch, PatchSet};
+    ///
+    /// let files = Arc::new(MemoryFileSystem::new().with_file("main.rs", "fn main() {}\n"));
+    ///
+    /// let mut set = PatchSet::new().with_file_system(files);
+    /// set.add(Patch::from_literal_target(
+    ///     "main.rs".to_string(),
+    ///     "main",
+    ///     BoundaryMode::Include,
+    ///     "start",
+    /// ));
+    ///
+    /// assert_eq!(set.apply_to_files().unwrap()["main.rs"], "fn start() {}\n");
+    /// ```
+    #[must_use]
+    pub fn with_file_system(mut self, file_system: Arc<dyn FileSystem>) -> Self {
+        self.file_system = file_system;
+        self
+    }
+
+    /// Returns where files are read from and written to.
+    #[must_use]
+    pub fn file_system(&self) -> &Arc<dyn FileSystem> {
+        &self.file_system
+    }
+
     /// Add a patch to this set.
     ///
     /// Patches are not applied until `apply_to_files` is called. Multiple patches
@@ -84,13 +351,25 @@ impl PatchSet {
 
     /// Apply all patches in this set to their target files.
     ///
-    /// Patches are grouped by file and all snippets are resolved before sorting.
-    /// Resolved ranges are validated for overlaps - if two patches with non-empty
-    /// replacements have overlapping resolved ranges, an error is returned.
+    /// File operations ([`Operation::CreateFile`], [`Operation::DeleteFile`] and
+    /// [`Operation::RenameFile`]) run first, in the order they were added. Text patches then
+    /// address files by the paths they have afterwards, so a created file can be edited in the
+    /// same set and a moved file is edited at its destination.
+    ///
+    /// Text patches are grouped by file and all patches are resolved to edits before sorting.
+    /// Their inserted text is first normalised to the set's [`LineEnding`].
+    /// Idempotent patches whose change is already present are skipped.
+    /// A patch that [depends on](Patch::depends_on) others is resolved only after they have
+    /// been applied, against the text they produced, and in [`ApplyMode::Sequential`] mode every
+    /// patch is resolved against the text the patches before it produced.
+    /// Overlapping resolved ranges are handled according to the set's [`OverlapPolicy`], by
+    /// default returning an error naming both patches.
     ///
-    /// Patches are then sorted by reverse character index (highest first) and applied
-    /// sequentially to maintain stable positions. The resulting file contents are
-    /// returned as a map from file path to content.
+    /// The edits are then sorted by ascending character index, insertions at a shared position
+    /// by descending [priority](Patch::priority) and then declaration order, and each file is
+    /// rebuilt from them in a single pass, so no edit shifts the positions of another. The
+    /// resulting file contents are returned as a map from file path to content. Files deleted
+    /// or moved away are not included; use [`PatchSet::apply_to_changes`] to see them.
     ///
     /// This method reads files from disk, applies all patches for that file, and
     /// returns the modified content. It does not write to disk - use the returned
@@ -98,13 +377,21 @@ impl PatchSet {
     ///
     /// # Errors
     ///
+    /// Errors caused by a particular patch are wrapped in [`PatchError::InPatch`], which cites the
+    /// patch's index, id and file; [`PatchError::root`] returns the underlying error.
+    ///
     /// Returns an error if:
+    /// - patch ids are repeated, a dependency names an unknown id, or dependencies form a cycle,
     /// - any file cannot be read,
+    /// - a file to create already exists, or a file to delete, move or edit does not,
+    /// - any boundary's target occurs a number of times other than its `expected_count`,
     /// - any snippet cannot be resolved,
-    /// - resolved ranges overlap with non-empty replacements,
+    /// - resolved ranges overlap and the overlap policy does not resolve the overlap,
     /// - or any patch has an invalid range.
     ///
-    /// If an error occurs, no files are modified.
+    /// If an error occurs, no files are modified. Under an [`Atomicity`] other than
+    /// [`Atomicity::Set`], errors caused by a particular patch do not abort the set: the results
+    /// leave out the failing patches (or their files), and [`PatchSet::apply`] returns the errors.
     ///
     /// # Examples
     ///
@@ -123,56 +410,680 @@ impl PatchSet {
     /// assert_eq!(results.get("tests/fixtures/sample.txt").unwrap(), "hello rust\n");
     /// ```
     pub fn apply_to_files(&self) -> Result<HashMap<String, String>, PatchError> {
-        let mut results = HashMap::new();
+        self.apply_to_files_with_inverse()
+            .map(|(results, _inverse)| results)
+    }
 
-        // Group patches by file
-        let mut by_file: HashMap<String, Vec<&Patch>> = HashMap::new();
+    /// Apply all patches in this set to contents held in memory, keyed by file path.
+    ///
+    /// Behaves exactly like [`PatchSet::apply_to_files`], with `files` in place of the set's
+    /// [`FileSystem`]: a patch to a path missing from `files` finds no file. Returns every file
+    /// after the patches, leaving out files deleted or moved away. Nothing is read from or
+    /// written to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`]. Under an [`Atomicity`] that
+    /// carries on past failing patches, returns the first of [`Outcome::failures`] instead of
+    /// the files partly patched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Patch, PatchSet};
+    ///
+    /// let files = HashMap::from([
+    ///     ("a.rs".to_string(), Rope::from_str("fn a() {}\n")),
+    ///     ("b.rs".to_string(), Rope::from_str("fn b() {}\n")),
+    /// ]);
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(Patch::from_literal_target(
+    ///     "a.rs".to_string(),
+    ///     "a()",
+    ///     BoundaryMode::Include,
+    ///     "alpha()",
+    /// ));
+    ///
+    /// let patched = set.apply_to_map(files).unwrap();
+    /// assert_eq!(patched["a.rs"].to_string(), "fn alpha() {}\n");
+    /// assert_eq!(patched["b.rs"].to_string(), "fn b() {}\n");
+    /// ```
+    pub fn apply_to_map(
+        &self,
+        mut files: HashMap<String, Rope>,
+    ) -> Result<HashMap<String, Rope>, PatchError> {
+        // Only the files the patches name are read, the rest are returned as they are
+        let memory = MemoryFileSystem::new();
         for patch in &self.patches {
-            by_file.entry(patch.file.clone()).or_default().push(patch);
+            let renamed = match &patch.operation {
+                Operation::RenameFile { to } => Some(to),
+                _ => None,
+            };
+            for file in std::iter::once(&patch.file).chain(renamed) {
+                if let Some(rope) = files.get(file) {
+                    memory.insert(file.clone(), rope.to_string());
+                }
+            }
         }
-
-        for (file, patches) in by_file {
-            let content = std::fs::read_to_string(&file).map_err(PatchError::IoError)?;
-            let rope = Rope::from_str(&content);
-
-            // Resolve all snippets to concrete ranges
-            let mut resolved: Vec<(&Patch, (usize, usize))> = Vec::new();
-            for patch in &patches {
-                let resolution = patch.snippet.resolve(&rope)?;
-                let range = (resolution.start, resolution.end);
-                resolved.push((patch, range));
+        let memory: Arc<dyn FileSystem> = Arc::new(memory);
+        let (outcome, _originals) = self.apply_in(&memory)?;
+        if let Some(failure) = outcome.failures.into_iter().next() {
+            return Err(failure);
+        }
+        for (file, change) in outcome.changes {
+            match change {
+                FileChange::Write(content) => {
+                    files.insert(file, Rope::from_str(&content));
+                }
+                FileChange::Delete => {
+                    files.remove(&file);
+                }
             }
+        }
+        Ok(files)
+    }
+
+    /// Apply all patches in this set to one text held in memory, whatever file they name.
+    ///
+    /// Each patch is applied as though `rope` were the contents of its file, with the set's
+    /// overlap checks, ordering and settings, as by [`PatchSet::apply_to_map`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`], and
+    /// [`PatchError::FileNotFound`] if a file operation deletes or moves the text away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Patch, PatchSet};
+    ///
+    /// let mut set = PatchSet::new();
+    /// let doc = "doc".to_string();
+    /// set.add(Patch::from_literal_target(doc.clone(), "cat", BoundaryMode::Include, "dog"));
+    /// set.add(Patch::from_literal_target(doc, "sat", BoundaryMode::Include, "sits"));
+    ///
+    /// let patched = set.apply_to_rope(&Rope::from_str("the cat sat")).unwrap();
+    /// assert_eq!(patched.to_string(), "the dog sits");
+    /// ```
+    pub fn apply_to_rope(&self, rope: &Rope) -> Result<Rope, PatchError> {
+        let Some(first) = self.patches.first() else {
+            return Ok(rope.clone());
+        };
+        let file = first.file.clone();
+        let set = PatchSet {
+            patches: self
+                .patches
+                .iter()
+                .map(|patch| Patch {
+                    file: file.clone(),
+                    ..patch.clone()
+                })
+                .collect(),
+            line_ending: self.line_ending,
+            mode: self.mode,
+            overlap_policy: self.overlap_policy,
+            atomicity: self.atomicity,
+            ..Self::new()
+        };
+        set.apply_to_map(HashMap::from([(file.clone(), rope.clone())]))?
+            .remove(&file)
+            .ok_or(PatchError::FileNotFound { file })
+    }
+
+    /// Apply all patches in this set, also returning the patch set that undoes them.
+    ///
+    /// Behaves exactly like [`PatchSet::apply_to_files`], and additionally records every edit
+    /// made. The returned inverse, applied to the patched contents, restores the original
+    /// contents exactly. Its patches select the changed regions by character index and expect
+    /// the patched text to be present, so they refuse to apply to a file that changed since.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("greeting.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "hello world\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(Patch::from_literal_target(path.clone(), "world", BoundaryMode::Include, "rust"));
+    ///
+    /// let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    /// std::fs::write(&path, &results[&path]).unwrap();
+    ///
+    /// let restored = inverse.apply_to_files().unwrap();
+    /// assert_eq!(restored[&path], "hello world\n");
+    /// ```
+    pub fn apply_to_files_with_inverse(
+        &self,
+    ) -> Result<(HashMap<String, String>, PatchSet), PatchError> {
+        let (changes, inverse) = self.apply_to_changes_with_inverse()?;
+        let results = changes
+            .into_iter()
+            .filter_map(|(file, change)| match change {
+                FileChange::Write(content) => Some((file, content)),
+                FileChange::Delete => None,
+            })
+            .collect();
+        Ok((results, inverse))
+    }
 
-            // Check for overlapping ranges with non-empty replacements
-            for i in 0..resolved.len() {
-                for j in (i + 1)..resolved.len() {
-                    let (patch1, range1) = resolved[i];
-                    let (patch2, range2) = resolved[j];
+    /// Apply all patches in this set, returning the change to make to each file.
+    ///
+    /// Like [`PatchSet::apply_to_files`], but files that are deleted, or moved away by a rename,
+    /// are included as [`FileChange::Delete`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{FileChange, Operation, Patch, PatchSet, Snippet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let old = dir.path().join("old.rs").to_string_lossy().to_string();
+    /// let new = dir.path().join("new.rs").to_string_lossy().to_string();
+    /// std::fs::write(&old, "fn f() {}\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(
+    ///     Patch::new(old.clone(), Snippet::All, "")
+    ///         .with_operation(Operation::RenameFile { to: new.clone() }),
+    /// );
+    ///
+    /// let changes = set.apply_to_changes().unwrap();
+    /// assert_eq!(changes[&old], FileChange::Delete);
+    /// assert_eq!(changes[&new], FileChange::Write("fn f() {}\n".to_string()));
+    /// ```
+    pub fn apply_to_changes(&self) -> Result<HashMap<String, FileChange>, PatchError> {
+        self.apply_to_changes_with_inverse()
+            .map(|(changes, _inverse)| changes)
+    }
 
-                    // Check if ranges overlap
-                    let overlaps = range1.0 < range2.1 && range2.0 < range1.1;
+    /// Apply all patches in this set, returning the change to make to each file and the patch
+    /// set that undoes them.
+    ///
+    /// Files edited in place are reverted region by region, as described for
+    /// [`PatchSet::apply_to_files_with_inverse`]. Files created, deleted or moved are reverted by
+    /// restoring their original state as a whole: a created file is deleted, a deleted file is
+    /// recreated, and a rename is undone by recreating the source and deleting the destination.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`].
+    pub fn apply_to_changes_with_inverse(
+        &self,
+    ) -> Result<(HashMap<String, FileChange>, PatchSet), PatchError> {
+        self.apply()
+            .map(|outcome| (outcome.changes, outcome.inverse))
+    }
 
-                    if overlaps && !patch1.replacement.is_empty() && !patch2.replacement.is_empty()
-                    {
-                        return Err(PatchError::OverlappingRanges { range1, range2 });
+    /// Apply all patches in this set, returning the change to make to each file, the patch set
+    /// that undoes them, and a report of what each patch did.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Patch, PatchSet, PatchStatus};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("main.rs").to_string_lossy().to_string();
+    /// std::fs::write(&path, "fn main() {}\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(Patch::from_literal_target(path.clone(), "main", BoundaryMode::Include, "start"));
+    /// set.add(Patch::from_literal_target(path.clone(), "fn", BoundaryMode::Include, "fn"));
+    ///
+    /// let outcome = set.apply().unwrap();
+    /// let rename = &outcome.report.patches[0];
+    /// assert_eq!(rename.status, PatchStatus::Applied);
+    /// assert_eq!((rename.removed(), rename.inserted.as_str()), ("main", "start"));
+    /// let span = rename.span.as_ref().unwrap();
+    /// assert_eq!((span.start.line, span.start.col), (0, 3));
+    /// assert_eq!(outcome.report.patches[1].status, PatchStatus::NoOp);
+    /// ```
+    pub fn apply(&self) -> Result<Outcome, PatchError> {
+        self.apply_in(&self.file_system)
+            .map(|(outcome, _originals)| outcome)
+    }
+
+    /// Applies all patches in this set to the files in `file_system`, also returning the
+    /// original contents of each file changed (`None` if it did not exist).
+    fn apply_in(
+        &self,
+        file_system: &Arc<dyn FileSystem>,
+    ) -> Result<(Outcome, Originals), PatchError> {
+        let levels = dependencies::levels(&self.patches)?;
+        let mut run = Run::new(self.atomicity, &self.patches);
+
+        let mut table = FileTable::new(file_system.as_ref());
+        for (index, patch) in self.patches.iter().enumerate() {
+            if patch.operation.is_file_operation() && !run.blocked(index)? {
+                let started = Instant::now();
+                match table.apply_file_operation(patch, self.line_ending) {
+                    Ok(status) => run.report(
+                        PatchReport::new(index, patch, status).with_elapsed(started.elapsed()),
+                    ),
+                    Err(e) => run.fail(index, e.in_patch(index, patch))?,
+                }
+            }
+        }
+
+        // Group text patches by file
+        let mut by_file: BTreeMap<&str, Vec<(usize, &Patch)>> = BTreeMap::new();
+        for (index, patch) in self.patches.iter().enumerate() {
+            if !patch.operation.is_file_operation() {
+                by_file.entry(&patch.file).or_default().push((index, patch));
+            }
+        }
+
+        // Load each file, normalising inserted text to the file's line ending
+        let mut files: HashMap<&str, (Rope, InverseLog)> = HashMap::new();
+        let mut normalised: Vec<(usize, Cow<Patch>)> = Vec::new();
+        for (file, patches) in by_file {
+            let Ok(rope) = table.edit(file) else {
+                for (index, patch) in patches {
+                    if let Err(e) = table.edit(file) {
+                        run.fail(index, e.in_patch(index, patch))?;
                     }
                 }
+                continue;
+            };
+            let terminator = self.line_ending.terminator(&rope);
+            normalised.extend(patches.into_iter().map(|(index, patch)| match terminator {
+                Some(terminator) => (index, line_ending::normalise_patch(patch, terminator)),
+                None => (index, Cow::Borrowed(patch)),
+            }));
+            files.insert(file, (rope, InverseLog::default()));
+        }
+
+        // Apply each batch against the text the previous batches produced, every patch after
+        // the patches it depends on
+        let mut patches: Vec<(usize, &Patch)> = normalised
+            .iter()
+            .map(|(index, patch)| (*index, patch.as_ref()))
+            .collect();
+        let batches: Vec<&[(usize, &Patch)]> = match self.mode {
+            ApplyMode::Snapshot => {
+                patches.sort_by_key(|(index, patch)| (levels[*index], &patch.file, *index));
+                patches
+                    .chunk_by(|(a, x), (b, y)| levels[*a] == levels[*b] && x.file == y.file)
+                    .collect()
             }
+            ApplyMode::Sequential => {
+                patches.sort_by_key(|(index, _)| (levels[*index], *index));
+                patches.chunks(1).collect()
+            }
+        };
+        for batch in batches {
+            let file = batch[0].1.file.as_str();
+            // Only patches to files that could be loaded were batched
+            let Some((rope, log)) = files.get_mut(file) else {
+                continue;
+            };
+            apply_batch(file, batch, self.overlap_policy, rope, log, &mut run)?;
+        }
+
+        let mut logs = HashMap::new();
+        for (file, (rope, log)) in files {
+            logs.insert(file.to_string(), log);
+            table.put(file.to_string(), rope);
+        }
+        let (reports, failures) = run.finish(&mut table, &mut logs);
 
-            // Sort by reverse position for stable application
-            resolved.sort_by_key(|(_, range)| std::cmp::Reverse(range.0));
+        let finished = table.finish(logs);
+        // The inverse restores original text, which must not be re-normalised
+        let inverse = PatchSet {
+            patches: finished.inverse,
+            line_ending: LineEnding::Verbatim,
+            ..Self::new()
+        };
 
-            // Apply patches in reverse order
-            let mut rope = rope;
-            for (patch, _) in resolved {
-                patch.apply(&mut rope)?;
+        let outcome = Outcome {
+            changes: finished.changes,
+            inverse,
+            report: ApplyReport { patches: reports },
+            failures,
+            stamps: finished.stamps,
+            file_system: Arc::clone(file_system),
+        };
+        Ok((outcome, finished.originals))
+    }
+
+    /// Apply all patches in this set and write the changes to its [`FileSystem`], all or
+    /// nothing.
+    ///
+    /// On the local disk, each new content is written to a temporary file beside its target
+    /// and flushed to disk before any target is touched. The temporary files are then renamed
+    /// over their targets, each rename replacing its file atomically, and files to delete are
+    /// removed last. If any step fails, the files already replaced are restored and any
+    /// directories created for new files are removed, so the tree is left as it was.
+    ///
+    /// Under an [`Atomicity`] other than [`Atomicity::Set`], the changes that succeeded are
+    /// written and the failures returned in [`Outcome::failures`].
+    ///
+    /// Before anything is written, every file to change is checked against how it was when it
+    /// was read, as by [`Outcome::verify`], so that changes another program saved in the
+    /// meantime are not overwritten.
+    ///
+    /// With a [`Backup`] set, the original contents of the changed files are saved in the same
+    /// commit as the changes, so the backups are only kept if every change is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PatchSet::apply_to_files`],
+    /// [`PatchError::FileChanged`] if a file changed after it was read,
+    /// [`PatchError::FileExists`] if a backup would replace an existing file, and
+    /// [`PatchError::WriteFailed`] if a file cannot be written, moved or removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Patch, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("greeting.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "hello world\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(Patch::from_literal_target(path.clone(), "world", BoundaryMode::Include, "rust"));
+    ///
+    /// set.write().unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello rust\n");
+    /// ```
+    pub fn write(&self) -> Result<Outcome, PatchError> {
+        let (mut outcome, originals) = self.apply_in(&self.file_system)?;
+        outcome.verify()?;
+        let file_system = self.file_system.as_ref();
+        let Some(backup) = &self.backup else {
+            file_system.commit(&outcome.changes)?;
+            return Ok(outcome);
+        };
+
+        // Committed with the changes, then taken out of them again
+        let backups = backup::changes(backup, file_system, &outcome.changes, &originals)?;
+        if let Some(file) = backups.keys().find(|f| outcome.changes.contains_key(*f)) {
+            return Err(PatchError::FileExists { file: file.clone() });
+        }
+        let files: Vec<String> = backups.keys().cloned().collect();
+        outcome.changes.extend(backups);
+        let committed = file_system.commit(&outcome.changes);
+        for file in files {
+            outcome.changes.remove(&file);
+        }
+        committed.map(|()| outcome)
+    }
+
+    /// Returns the patches in this set, in the order they were added.
+    #[must_use]
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    /// Serialise this patch set as JSON.
+    ///
+    /// A set with the default [`ApplyMode`], [`LineEnding`], [`OverlapPolicy`] and [`Atomicity`]
+    /// is written as a plain array of patches; otherwise it is written as an object with `mode`,
+    /// `line_ending`, `overlap_policy`, `atomicity` and `patches` keys. Either is the format the
+    /// CLI reads, so an inverse patch set can be saved and applied later to undo a change.
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        if self.mode == ApplyMode::default()
+            && self.line_ending == LineEnding::default()
+            && self.overlap_policy == OverlapPolicy::default()
+            && self.atomicity == Atomicity::default()
+        {
+            facet_json::to_string(&self.patches)
+        } else {
+            facet_json::to_string(&PatchSetDocument {
+                mode: self.mode,
+                line_ending: self.line_ending,
+                overlap_policy: self.overlap_policy,
+                atomicity: self.atomicity,
+                patches: self.patches.clone(),
+            })
+        }
+    }
+
+    /// Parse a patch set from JSON.
+    ///
+    /// Accepts either a plain array of patches, or an object with a `patches` array and
+    /// optional `mode`, `line_ending`, `overlap_policy` and `atomicity` settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::IoError`] with kind `InvalidData` if the JSON is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{ApplyMode, PatchSet};
+    ///
+    /// let set = PatchSet::from_json(r#"[{"file": "a.txt", "snippet": "All", "replacement": "x"}]"#)
+    ///     .unwrap();
+    /// assert_eq!(set.patches().len(), 1);
+    /// assert_eq!(PatchSet::from_json(&set.to_json()).unwrap().patches()[0].file, "a.txt");
+    ///
+    /// let set = PatchSet::from_json(r#"{"mode": "Sequential", "patches": []}"#).unwrap();
+    /// assert_eq!(set.mode(), ApplyMode::Sequential);
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, PatchError> {
+        if json.trim_start().starts_with('{') {
+            let document: PatchSetDocument = facet_json::from_str(json)?;
+            Ok(Self {
+                patches: document.patches,
+                line_ending: document.line_ending,
+                mode: document.mode,
+                overlap_policy: document.overlap_policy,
+                atomicity: document.atomicity,
+                backup: None,
+                file_system: Arc::new(StdFileSystem),
+            })
+        } else {
+            let patches: Vec<Patch> = facet_json::from_str(json)?;
+            Ok(Self {
+                patches,
+                ..Self::new()
+            })
+        }
+    }
+}
+
+/// The JSON object form of a patch set, carrying its settings alongside the patches.
+#[cfg(feature = "json")]
+#[derive(Facet)]
+struct PatchSetDocument {
+    /// Whether patches to the same file see each other's changes.
+    #[facet(default)]
+    mode: ApplyMode,
+    /// The line ending replacement text is normalised to.
+    #[facet(default)]
+    line_ending: LineEnding,
+    /// How overlapping edits are handled.
+    #[facet(default)]
+    overlap_policy: OverlapPolicy,
+    /// How much of the set a failing patch takes down with it.
+    #[facet(default)]
+    atomicity: Atomicity,
+    /// The patches in the set.
+    patches: Vec<Patch>,
+}
+
+/// Applies a batch of patches to a rope, resolving them all against its current text.
+///
+/// Overlapping edits are handled according to `policy`, then the rope is rebuilt from the
+/// resolved edits without resolving any patch again, with insertions at a shared position
+/// ordered as documented on [`PatchSet`]. Errors are wrapped with the identity of the patch that
+/// caused them and recorded in `run`, which decides whether they abort the set. Of two
+/// overlapping patches, the later one fails.
+fn apply_batch(
+    file: &str,
+    patches: &[(usize, &Patch)],
+    policy: OverlapPolicy,
+    rope: &mut Rope,
+    log: &mut InverseLog,
+    run: &mut Run,
+) -> Result<(), PatchError> {
+    // Resolve all patches to concrete edits, reporting what each edit would do
+    let mut resolved = Vec::new();
+    let mut reports = Vec::with_capacity(patches.len());
+    for &(index, patch) in patches {
+        if run.blocked(index)? {
+            continue;
+        }
+        let started = Instant::now();
+        let edit = match resolve_checked(file, patch, rope) {
+            Ok(Some(edit)) if edit.start > edit.end || edit.end > rope.len_chars() => {
+                Err(PatchError::RangeOutOfBounds)
             }
+            result => result,
+        };
+        let elapsed = started.elapsed();
+
+        let mut report = PatchReport::new(index, patch, PatchStatus::Skipped);
+        match edit {
+            Ok(Some(edit)) => {
+                report.status = if edit.is_no_op(rope) {
+                    PatchStatus::NoOp
+                } else {
+                    PatchStatus::Applied
+                };
+                report.span = Some(Span::new(rope, edit.start, edit.end));
+                report.inserted.clone_from(&edit.text);
+                resolved.push(Resolved::new(index, patch, edit));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                run.fail(index, e.in_patch(index, patch))?;
+                continue;
+            }
+        }
+        reports.push(report.with_elapsed(elapsed));
+    }
+
+    while let Err(error) = overlap::resolve_overlaps(file, &mut resolved, policy) {
+        let PatchError::OverlappingRanges { patches, .. } = &error else {
+            return Err(error);
+        };
+        let later = patches[1].index;
+        resolved.retain(|r| r.index != later);
+        run.fail(later, error)?;
+    }
+    if run.file_failed(file) {
+        // The file is left unchanged, so the batch is not applied
+        for report in reports {
+            run.report(report);
+        }
+        return Ok(());
+    }
 
-            results.insert(file, rope.to_string());
+    // Patches whose edits the policy dropped did nothing
+    let kept: HashSet<usize> = resolved
+        .iter()
+        .flat_map(|r| r.absorbed.iter().copied().chain([r.index]))
+        .collect();
+    for mut report in reports {
+        if run.has_failed(report.index) {
+            continue;
         }
+        if !kept.contains(&report.index) {
+            report.status = PatchStatus::Skipped;
+        }
+        run.report(report);
+    }
+
+    // Sort once into the order the edits appear in the output. At a shared start, insertions
+    // come before the range replaced there, by descending priority, then declaration order.
+    resolved.sort_by_key(|r| (r.edit.start, r.edit.end, Reverse(r.patch.priority), r.index));
 
-        Ok(results)
+    // Logging from the highest position down reads only text that no logged edit has moved, so
+    // every edit is logged against the original rope
+    for r in resolved.iter().rev() {
+        log.record(rope, &r.edit);
     }
+    *rope = rebuild(rope, &resolved);
+    Ok(())
+}
+
+/// Builds the text of `rope` with sorted, non-overlapping edits applied, in a single pass.
+fn rebuild(rope: &Rope, resolved: &[Resolved]) -> Rope {
+    let mut builder = RopeBuilder::new();
+    let mut cursor = 0;
+    for Resolved { edit, .. } in resolved {
+        for chunk in rope.slice(cursor..edit.start).chunks() {
+            builder.append(chunk);
+        }
+        builder.append(&edit.text);
+        cursor = edit.end;
+    }
+    for chunk in rope.slice(cursor..).chunks() {
+        builder.append(chunk);
+    }
+    builder.finish()
+}
+
+/// Resolves a patch to its edit after validating its boundaries' match counts.
+///
+/// An idempotent patch that is already applied resolves to `None`; its targets may legitimately
+/// have disappeared, so counts are only checked once it is known to need applying.
+fn resolve_checked(file: &str, patch: &Patch, rope: &Rope) -> Result<Option<Edit>, PatchError> {
+    if patch.idempotent {
+        let edit = patch.resolve(rope)?;
+        if edit.is_some() {
+            check_match_counts(file, patch, rope)?;
+        }
+        Ok(edit)
+    } else {
+        check_match_counts(file, patch, rope)?;
+        patch.resolve(rope)
+    }
+}
+
+/// Validates the `expected_count` of every boundary in a patch's snippet.
+///
+/// Operations that ignore the snippet, such as [`Operation::Prepend`], have no counts to check.
+///
+/// # Errors
+///
+/// Returns [`PatchError::MatchCountMismatch`] with the actual occurrences if any constraint fails.
+fn check_match_counts(file: &str, patch: &Patch, rope: &Rope) -> Result<(), PatchError> {
+    if patch.operation.is_file_level() {
+        return Ok(());
+    }
+    for boundary in patch.snippet.boundaries() {
+        let Some(expected) = &boundary.expected_count else {
+            continue;
+        };
+        let positions = boundary.target.resolve_all(rope)?;
+        if !expected.admits(positions.len()) {
+            return Err(PatchError::MatchCountMismatch {
+                file: file.to_string(),
+                target: boundary.target.clone(),
+                expected: expected.clone(),
+                actual: positions.len(),
+                positions,
+            });
+        }
+    }
+    Ok(())
 }
 
 impl Default for PatchSet {
@@ -180,3 +1091,47 @@ impl Default for PatchSet {
         Self::new()
     }
 }
+
+#[cfg(test)]
+#[path = "tests/helpers.rs"]
+mod test_helpers;
+
+#[cfg(test)]
+#[path = "tests/match_count.rs"]
+mod match_count;
+
+#[cfg(test)]
+#[path = "tests/inverse.rs"]
+mod inverse_patches;
+
+#[cfg(test)]
+#[path = "tests/file_operations.rs"]
+mod file_operations;
+
+#[cfg(test)]
+#[path = "tests/dependencies.rs"]
+mod dependencies_tests;
+
+#[cfg(test)]
+#[path = "tests/apply_mode.rs"]
+mod apply_mode;
+
+#[cfg(test)]
+#[path = "tests/overlap_policy.rs"]
+mod overlap_policy;
+
+#[cfg(test)]
+#[path = "tests/insertion_order.rs"]
+mod insertion_order;
+
+#[cfg(test)]
+#[path = "tests/apply_report.rs"]
+mod apply_report;
+
+#[cfg(test)]
+#[path = "tests/atomicity.rs"]
+mod atomicity_tests;
+
+#[cfg(test)]
+#[path = "tests/in_memory.rs"]
+mod in_memory;
@@ -0,0 +1,208 @@
+//! How much of a patch set a failing patch takes down with it.
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+use super::files::FileTable;
+use super::inverse::InverseLog;
+use super::report::PatchReport;
+use crate::patch::{Operation, Patch, PatchError, PatchStatus};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// The unit a patch set applies all-or-nothing.
+///
+/// Below [`Atomicity::Set`], a failing patch is reported with [`PatchStatus::Failed`] and its
+/// error collected in [`Outcome::failures`](crate::Outcome::failures), and the set carries on.
+/// A patch that depends on a patch that was not applied fails with
+/// [`PatchError::DependencyFailed`].
+pub enum Atomicity {
+    /// Any failure aborts the whole set, leaving every file unchanged.
+    #[default]
+    Set,
+    /// A failure leaves the failing patch's file unchanged, along with the files it was renamed
+    /// to or from, and every other file is patched.
+    File,
+    /// Every patch that resolves cleanly is applied, and failing patches are left out.
+    Patch,
+}
+
+/// The reports and failures gathered while a patch set is applied.
+pub(crate) struct Run<'a> {
+    /// How far a failure reaches.
+    atomicity: Atomicity,
+    /// The patches in the set.
+    patches: &'a [Patch],
+    /// Reports of the patches attempted so far, in no particular order.
+    reports: Vec<PatchReport>,
+    /// Errors of the patches that failed, with their indices.
+    errors: Vec<(usize, PatchError)>,
+    /// Indices of the patches that failed.
+    failed: HashSet<usize>,
+    /// Ids of the patches that failed.
+    failed_ids: HashSet<&'a str>,
+    /// Files left unchanged because a patch to them failed, under [`Atomicity::File`].
+    failed_files: HashSet<&'a str>,
+}
+
+impl<'a> Run<'a> {
+    /// Starts a run of `patches`.
+    pub(crate) fn new(atomicity: Atomicity, patches: &'a [Patch]) -> Self {
+        Self {
+            atomicity,
+            patches,
+            reports: Vec::with_capacity(patches.len()),
+            errors: Vec::new(),
+            failed: HashSet::new(),
+            failed_ids: HashSet::new(),
+            failed_files: HashSet::new(),
+        }
+    }
+
+    /// Adds the report of a patch that was attempted.
+    pub(crate) fn report(&mut self, report: PatchReport) {
+        self.reports.push(report);
+    }
+
+    /// Returns whether the patch at `index` must not be attempted, reporting why.
+    ///
+    /// A patch is skipped when its file has already failed, and fails when one of its
+    /// dependencies did.
+    ///
+    /// # Errors
+    ///
+    /// Returns the dependency failure if the whole set must abort, which cannot happen before
+    /// another failure was recorded.
+    pub(crate) fn blocked(&mut self, index: usize) -> Result<bool, PatchError> {
+        let patch = &self.patches[index];
+        if self.failed_files.contains(patch.file.as_str()) {
+            self.report(PatchReport::new(index, patch, PatchStatus::Skipped));
+            return Ok(true);
+        }
+        let failed_dependency = patch
+            .depends_on
+            .iter()
+            .find(|id| self.failed_ids.contains(id.as_str()));
+        if let Some(id) = failed_dependency {
+            let error = PatchError::DependencyFailed { id: id.clone() }.in_patch(index, patch);
+            self.fail(index, error)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Records that the patch at `index` failed with `error`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `error` itself under [`Atomicity::Set`], to abort the set.
+    pub(crate) fn fail(&mut self, index: usize, error: PatchError) -> Result<(), PatchError> {
+        if self.atomicity == Atomicity::Set {
+            return Err(error);
+        }
+        let patch = &self.patches[index];
+        let mut report = PatchReport::new(index, patch, PatchStatus::Failed);
+        report.error = Some(error.to_string());
+        self.reports.push(report);
+        self.errors.push((index, error));
+        self.failed.insert(index);
+        if let Some(id) = &patch.id {
+            self.failed_ids.insert(id);
+        }
+        if self.atomicity == Atomicity::File {
+            self.failed_files.insert(&patch.file);
+            if let Operation::RenameFile { to } = &patch.operation {
+                self.failed_files.insert(to);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the patch at `index` has failed.
+    pub(crate) fn has_failed(&self, index: usize) -> bool {
+        self.failed.contains(&index)
+    }
+
+    /// Returns whether a patch to `file` has failed, leaving the file unchanged.
+    pub(crate) fn file_failed(&self, file: &str) -> bool {
+        self.failed_files.contains(file)
+    }
+
+    /// Reverts the files that failed, returning the reports and the failures in patch order.
+    ///
+    /// Under [`Atomicity::File`], reverting a file also reverts the files it was renamed to or
+    /// from, and the files of patches that depend on a patch to it, until no more fail. The
+    /// patches to reverted files that did not fail themselves are reported as skipped.
+    pub(crate) fn finish(
+        mut self,
+        table: &mut FileTable<'_>,
+        logs: &mut HashMap<String, InverseLog>,
+    ) -> (Vec<PatchReport>, Vec<PatchError>) {
+        if !self.failed_files.is_empty() {
+            self.spread_file_failures();
+            for file in &self.failed_files {
+                table.revert(file);
+                logs.remove(*file);
+            }
+            for report in &mut self.reports {
+                let patch = &self.patches[report.index];
+                if report.status != PatchStatus::Failed && touches(&self.failed_files, patch) {
+                    report.status = PatchStatus::Skipped;
+                }
+            }
+        }
+        self.reports.sort_by_key(|report| report.index);
+        self.errors.sort_by_key(|(index, _)| *index);
+        let errors = self.errors.into_iter().map(|(_, error)| error).collect();
+        (self.reports, errors)
+    }
+
+    /// Marks as failed every file tied to a failed file by a rename or a dependency.
+    fn spread_file_failures(&mut self) {
+        let applied: HashSet<usize> = self
+            .reports
+            .iter()
+            .filter(|r| matches!(r.status, PatchStatus::Applied | PatchStatus::NoOp))
+            .map(|r| r.index)
+            .collect();
+        loop {
+            let reverted: HashSet<&str> = self
+                .patches
+                .iter()
+                .filter(|p| touches(&self.failed_files, p))
+                .filter_map(|p| p.id.as_deref())
+                .collect();
+            let mut spread = Vec::new();
+            for (index, patch) in self.patches.iter().enumerate() {
+                if !applied.contains(&index) {
+                    continue;
+                }
+                let tied = touches(&self.failed_files, patch)
+                    || patch
+                        .depends_on
+                        .iter()
+                        .any(|id| reverted.contains(id.as_str()));
+                if tied {
+                    spread.push(patch.file.as_str());
+                    if let Operation::RenameFile { to } = &patch.operation {
+                        spread.push(to);
+                    }
+                }
+            }
+            let before = self.failed_files.len();
+            self.failed_files.extend(spread);
+            if self.failed_files.len() == before {
+                break;
+            }
+        }
+    }
+}
+
+/// Returns whether `patch` changes one of `files`.
+fn touches(files: &HashSet<&str>, patch: &Patch) -> bool {
+    files.contains(patch.file.as_str())
+        || matches!(&patch.operation, Operation::RenameFile { to } if files.contains(to.as_str()))
+}
@@ -0,0 +1,369 @@
+//! Keeping the original contents of the files a patch set writes, and restoring them.
+//!
+//! Backups are written through the set's [`FileSystem`], in the same commit as the changes they
+//! back up, so either both are in place or neither is.
+//!
+//! A journal is a directory of plain files. Its `index` lists the runs recorded, one id per
+//! line, oldest first. Each run has a `<id>.manifest` with one line per file changed, giving the
+//! SHA-256 of the file before and after the run (`-` where it did not exist) and its path, and a
+//! `<id>.<n>` holding the original contents of the file on line `n` of the manifest, counting
+//! from zero, if it existed.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::file_system::{FileSystem, StdFileSystem};
+use super::files::Originals;
+use super::FileChange;
+use crate::patch::{content_hash, PatchError};
+
+/// Where [`PatchSet::write`](crate::PatchSet::write) keeps the original contents of the files
+/// it changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backup {
+    /// Copy each file that is replaced or deleted beside itself, with an `.orig` suffix.
+    ///
+    /// An existing `.orig` file is never replaced: writing fails instead, so the first backup
+    /// of a file is kept until it is removed.
+    Suffix,
+    /// Record each run in a [`Journal`] in this directory, from which it can be undone.
+    Journal(PathBuf),
+}
+
+impl Backup {
+    /// Record each run in a journal in `.textum/`, relative to the working directory.
+    #[must_use]
+    pub fn journal() -> Self {
+        Self::Journal(PathBuf::from(Journal::DEFAULT_DIR))
+    }
+}
+
+/// Returns the files to write alongside `changes` to keep the originals as `backup` directs.
+///
+/// # Errors
+///
+/// Returns [`PatchError::FileExists`] if a suffixed backup would replace an existing file, or an
+/// I/O error if it or the journal cannot be read.
+pub(crate) fn changes(
+    backup: &Backup,
+    file_system: &dyn FileSystem,
+    changes: &HashMap<String, FileChange>,
+    originals: &Originals,
+) -> Result<HashMap<String, FileChange>, PatchError> {
+    let mut backups = HashMap::new();
+    match backup {
+        Backup::Suffix => {
+            for (file, original) in originals {
+                if let Some(content) = original {
+                    let path = format!("{file}.orig");
+                    if file_system.read(&path)?.is_some() {
+                        return Err(PatchError::FileExists { file: path });
+                    }
+                    backups.insert(path, FileChange::Write(content.clone()));
+                }
+            }
+        }
+        Backup::Journal(dir) => {
+            let journal = Journal::new(dir.clone());
+            let mut index = journal.index(file_system)?;
+            let id = new_run_id(&index);
+            let mut manifest = String::new();
+            for (position, (file, original)) in originals.iter().enumerate() {
+                let written = match changes.get(file) {
+                    Some(FileChange::Write(content)) => Some(content_hash(content)),
+                    Some(FileChange::Delete) | None => None,
+                };
+                if let Some(content) = original {
+                    let path = journal.path(&format!("{id}.{position}"));
+                    backups.insert(path, FileChange::Write(content.clone()));
+                }
+                let before = original.as_deref().map(content_hash);
+                let _ = writeln!(
+                    manifest,
+                    "{} {} {}",
+                    before.as_deref().unwrap_or("-"),
+                    written.as_deref().unwrap_or("-"),
+                    escape(file),
+                );
+            }
+            backups.insert(
+                journal.path(&format!("{id}.manifest")),
+                FileChange::Write(manifest),
+            );
+            index.push(id);
+            backups.insert(journal.path("index"), FileChange::Write(join_lines(&index)));
+        }
+    }
+    Ok(backups)
+}
+
+/// Returns an id for a new run, named by the time so that it sorts after the runs in `index`.
+fn new_run_id(index: &[String]) -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let id = format!("{millis:016}-000");
+    let Some(last) = index.last().filter(|last| id <= **last) else {
+        return id;
+    };
+    // Several runs in one millisecond, or the clock went back: follow on from the last run
+    match last.rsplit_once('-').map(|(at, n)| (at, n.parse::<u32>())) {
+        Some((at, Ok(attempt))) => format!("{at}-{:03}", attempt + 1),
+        _ => format!("{last}-000"),
+    }
+}
+
+/// Returns `lines` joined with a `\n` after each.
+fn join_lines(lines: &[String]) -> String {
+    lines.iter().fold(String::new(), |mut joined, line| {
+        let _ = writeln!(joined, "{line}");
+        joined
+    })
+}
+
+/// The runs recorded by [`Backup::Journal`] in one directory.
+///
+/// File paths are recorded as the patches gave them, so runs of patches with relative paths
+/// must be restored from the same working directory.
+///
+/// # Examples
+///
+/// ```
+/// use textum::{Backup, BoundaryMode, Journal, Patch, PatchSet};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("greeting.txt").to_string_lossy().to_string();
+/// std::fs::write(&path, "hello world\n").unwrap();
+/// let journal_dir = dir.path().join(".textum");
+///
+/// let mut set = PatchSet::new().with_backup(Backup::Journal(journal_dir.clone()));
+/// set.add(Patch::from_literal_target(path.clone(), "world", BoundaryMode::Include, "rust"));
+/// set.write().unwrap();
+///
+/// let run = Journal::new(journal_dir).last().unwrap().unwrap();
+/// run.restore().unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Journal {
+    /// The directory holding the runs.
+    dir: PathBuf,
+    /// Where the journal and the files it records are read and written.
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl Journal {
+    /// The directory [`Backup::journal`] records runs in.
+    pub const DEFAULT_DIR: &'static str = ".textum";
+
+    /// Opens the journal in `dir` on the local disk, which need not exist yet.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            file_system: Arc::new(StdFileSystem),
+        }
+    }
+
+    /// Set where the journal and the files it records are read and restored, which should be
+    /// the [`FileSystem`] of the patch sets that recorded it.
+    #[must_use]
+    pub fn with_file_system(mut self, file_system: Arc<dyn FileSystem>) -> Self {
+        self.file_system = file_system;
+        self
+    }
+
+    /// Returns the runs recorded, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the journal cannot be read or a manifest is malformed.
+    pub fn runs(&self) -> Result<Vec<JournalRun>, PatchError> {
+        self.index(self.file_system.as_ref())?
+            .into_iter()
+            .map(|id| self.load(id))
+            .collect()
+    }
+
+    /// Returns the most recent run, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Journal::runs`].
+    pub fn last(&self) -> Result<Option<JournalRun>, PatchError> {
+        let index = self.index(self.file_system.as_ref())?;
+        index.last().map(|id| self.load(id.clone())).transpose()
+    }
+
+    /// Returns the path of a file in the journal.
+    fn path(&self, name: &str) -> String {
+        Path::new(&self.dir)
+            .join(name)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Reads the ids of the runs recorded in `file_system`, oldest first.
+    fn index(&self, file_system: &dyn FileSystem) -> io::Result<Vec<String>> {
+        let index = file_system.read(&self.path("index"))?;
+        Ok(index.map_or_else(Vec::new, |(content, _stamp)| {
+            content.lines().map(str::to_string).collect()
+        }))
+    }
+
+    /// Reads the run recorded as `id`.
+    fn load(&self, id: String) -> Result<JournalRun, PatchError> {
+        let manifest_path = self.path(&format!("{id}.manifest"));
+        let malformed = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Malformed journal manifest {manifest_path}"),
+            )
+        };
+        let (manifest, _stamp) = self
+            .file_system
+            .read(&manifest_path)?
+            .ok_or_else(malformed)?;
+        let files = manifest
+            .lines()
+            .map(parse_entry)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(malformed)?;
+        Ok(JournalRun {
+            id,
+            files,
+            journal: self.clone(),
+        })
+    }
+}
+
+/// One run of a patch set recorded in a [`Journal`].
+#[derive(Debug, Clone)]
+pub struct JournalRun {
+    /// The run's name in the journal, which sorts after every earlier run's.
+    pub id: String,
+    /// The files the run changed, in path order.
+    pub files: Vec<JournalEntry>,
+    /// The journal the run is recorded in.
+    journal: Journal,
+}
+
+/// A file changed by a [`JournalRun`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// The file's path.
+    pub file: String,
+    /// SHA-256 of the file before the run, `None` if it did not exist.
+    pub original_hash: Option<String>,
+    /// SHA-256 of the file the run wrote, `None` if the run deleted it.
+    pub written_hash: Option<String>,
+}
+
+impl JournalRun {
+    /// Checks that every file is still as the run left it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::FileChanged`] for the first file modified, created or deleted
+    /// since, or an I/O error if a file cannot be read.
+    pub fn check(&self) -> Result<(), PatchError> {
+        for entry in &self.files {
+            let current = self.journal.file_system.read(&entry.file)?;
+            let current = current.map(|(_content, stamp)| stamp.hash);
+            if current != entry.written_hash {
+                return Err(PatchError::FileChanged {
+                    file: entry.file.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts every file back as it was before the run, and removes the run from the journal.
+    ///
+    /// The files are restored and the run removed in one commit to the journal's
+    /// [`FileSystem`], all-or-nothing, as by [`PatchSet::write`](crate::PatchSet::write).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::FileChanged`] without restoring anything if any file was modified
+    /// since the run, [`PatchError::WriteFailed`] if a file cannot be restored, or an I/O error
+    /// if the journal cannot be read.
+    pub fn restore(self) -> Result<(), PatchError> {
+        self.check()?;
+        let journal = &self.journal;
+        let file_system = journal.file_system.as_ref();
+        let mut changes = HashMap::new();
+        for (position, entry) in self.files.iter().enumerate() {
+            let change = if entry.original_hash.is_some() {
+                let path = journal.path(&format!("{}.{position}", self.id));
+                changes.insert(path.clone(), FileChange::Delete);
+                let (original, _stamp) = file_system.read(&path)?.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, format!("Missing backup {path}"))
+                })?;
+                FileChange::Write(original)
+            } else {
+                FileChange::Delete
+            };
+            if entry.original_hash != entry.written_hash {
+                changes.insert(entry.file.clone(), change);
+            }
+        }
+        changes.insert(
+            journal.path(&format!("{}.manifest", self.id)),
+            FileChange::Delete,
+        );
+        let mut index = journal.index(file_system)?;
+        index.retain(|id| *id != self.id);
+        changes.insert(journal.path("index"), FileChange::Write(join_lines(&index)));
+        file_system.commit(&changes)
+    }
+}
+
+/// Parses a manifest line, `None` if it is malformed.
+fn parse_entry(line: &str) -> Option<JournalEntry> {
+    let mut fields = line.splitn(3, ' ');
+    let hash = |field: &str| (field != "-").then(|| field.to_string());
+    let original_hash = hash(fields.next()?);
+    let written_hash = hash(fields.next()?);
+    let file = unescape(fields.next()?)?;
+    Some(JournalEntry {
+        file,
+        original_hash,
+        written_hash,
+    })
+}
+
+/// Escapes backslashes and line breaks, so a path fits on one manifest line.
+fn escape(file: &str) -> String {
+    file.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Reverses [`escape`], `None` if `escaped` is not its output.
+fn unescape(escaped: &str) -> Option<String> {
+    let mut file = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            file.push(match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            });
+        } else {
+            file.push(c);
+        }
+    }
+    Some(file)
+}
+
+#[cfg(test)]
+#[path = "../tests/backup.rs"]
+mod backup_tests;
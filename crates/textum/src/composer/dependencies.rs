@@ -0,0 +1,70 @@
+//! Ordering patches by their declared dependencies.
+//!
+//! Patches are assigned to levels: a patch without dependencies is at level zero, and any other
+//! patch is one level above the highest of its dependencies. A patch set applies each level as a
+//! batch against the text the previous levels produced.
+
+use std::collections::HashMap;
+
+use crate::patch::{Patch, PatchError};
+
+/// Returns the dependency level of each patch, in the order given.
+///
+/// # Errors
+///
+/// Returns [`PatchError::DuplicateId`] or [`PatchError::UnknownDependency`] (wrapped with the
+/// offending patch) if ids are repeated or missing, and [`PatchError::DependencyCycle`] if the
+/// dependencies form a cycle.
+pub(crate) fn levels(patches: &[Patch]) -> Result<Vec<usize>, PatchError> {
+    let mut by_id = HashMap::new();
+    for (index, patch) in patches.iter().enumerate() {
+        if let Some(id) = &patch.id {
+            if by_id.insert(id.as_str(), index).is_some() {
+                return Err(PatchError::DuplicateId { id: id.clone() }.in_patch(index, patch));
+            }
+        }
+    }
+
+    let mut levels = vec![None; patches.len()];
+    for index in 0..patches.len() {
+        let mut path = Vec::new();
+        level_of(index, patches, &by_id, &mut levels, &mut path)?;
+    }
+    Ok(levels.into_iter().map(Option::unwrap_or_default).collect())
+}
+
+/// Computes the level of the patch at `index`, memoising levels of its dependencies.
+///
+/// `path` holds the patches currently being visited, to detect cycles.
+fn level_of(
+    index: usize,
+    patches: &[Patch],
+    by_id: &HashMap<&str, usize>,
+    levels: &mut [Option<usize>],
+    path: &mut Vec<usize>,
+) -> Result<usize, PatchError> {
+    if let Some(level) = levels[index] {
+        return Ok(level);
+    }
+    if let Some(pos) = path.iter().position(|&i| i == index) {
+        let ids = path[pos..]
+            .iter()
+            .map(|&i| patches[i].id.clone().unwrap_or_default())
+            .collect();
+        return Err(PatchError::DependencyCycle { ids });
+    }
+
+    path.push(index);
+    let mut level = 0;
+    for id in &patches[index].depends_on {
+        let Some(&dependency) = by_id.get(id.as_str()) else {
+            let err = PatchError::UnknownDependency { id: id.clone() };
+            return Err(err.in_patch(index, &patches[index]));
+        };
+        level = level.max(level_of(dependency, patches, by_id, levels, path)? + 1);
+    }
+    path.pop();
+
+    levels[index] = Some(level);
+    Ok(level)
+}
@@ -0,0 +1,190 @@
+//! Where a patch set reads the files it patches and writes its changes.
+//!
+//! By default files live on the local disk ([`StdFileSystem`]). Editors, build systems and
+//! tests can instead apply a set to unsaved buffers or an in-memory tree, through
+//! [`MemoryFileSystem`] or their own [`FileSystem`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use super::stamp::FileStamp;
+use super::{transaction, FileChange};
+use crate::patch::{content_hash, PatchError};
+
+/// A store of text files that a patch set reads from and writes to.
+///
+/// Files are named by the paths patches give them, which each store interprets as it sees fit.
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Reads a file, returning its contents and stamp, or `None` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file exists but cannot be read as text.
+    fn read(&self, file: &str) -> io::Result<Option<(String, FileStamp)>>;
+
+    /// Returns whether `file` is still as `stamp` recorded it when it was read, `None` meaning
+    /// it did not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be inspected.
+    fn unchanged(&self, file: &str, stamp: Option<&FileStamp>) -> io::Result<bool>;
+
+    /// Makes every change in `changes`, or none of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::WriteFailed`] naming a file that could not be written or removed,
+    /// having left every file as it was.
+    fn commit(&self, changes: &HashMap<String, FileChange>) -> Result<(), PatchError>;
+}
+
+/// The local disk, through [`std::fs`].
+///
+/// Changes are committed by writing each file to a temporary file beside it and renaming it
+/// into place, as described for [`PatchSet::write`](crate::PatchSet::write).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn read(&self, file: &str) -> io::Result<Option<(String, FileStamp)>> {
+        FileStamp::read(file)
+    }
+
+    fn unchanged(&self, file: &str, stamp: Option<&FileStamp>) -> io::Result<bool> {
+        match (fs::metadata(file), stamp) {
+            (Ok(metadata), Some(stamp)) => stamp.matches(file, &metadata),
+            (Ok(_), None) => Ok(false),
+            (Err(e), stamp) if e.kind() == io::ErrorKind::NotFound => Ok(stamp.is_none()),
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    fn commit(&self, changes: &HashMap<String, FileChange>) -> Result<(), PatchError> {
+        transaction::commit(changes)
+    }
+}
+
+/// Files held in memory, keyed by path.
+///
+/// Shared behind an [`Arc`](std::sync::Arc), the same files can be given to a patch set and
+/// inspected after it is written. Files are stamped by their length and hash alone.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use textum::{BoundaryMode, MemoryFileSystem, Patch, PatchSet};
+///
+/// let files = Arc::new(MemoryFileSystem::new().with_file("greeting.txt", "hello world\n"));
+///
+/// let mut set = PatchSet::new().with_file_system(files.clone());
+/// set.add(Patch::from_literal_target(
+///     "greeting.txt".to_string(),
+///     "world",
+///     BoundaryMode::Include,
+///     "rust",
+/// ));
+/// set.write().unwrap();
+///
+/// assert_eq!(files.get("greeting.txt").unwrap(), "hello rust\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    /// The contents of each file.
+    files: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemoryFileSystem {
+    /// Create an empty file system.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file with the given contents.
+    #[must_use]
+    pub fn with_file(self, file: impl Into<String>, content: impl Into<String>) -> Self {
+        self.insert(file, content);
+        self
+    }
+
+    /// Creates or replaces a file.
+    pub fn insert(&self, file: impl Into<String>, content: impl Into<String>) {
+        self.lock().insert(file.into(), content.into());
+    }
+
+    /// Removes a file, returning its contents if it existed.
+    pub fn remove(&self, file: &str) -> Option<String> {
+        self.lock().remove(file)
+    }
+
+    /// Returns the contents of a file, if it exists.
+    #[must_use]
+    pub fn get(&self, file: &str) -> Option<String> {
+        self.lock().get(file).cloned()
+    }
+
+    /// Returns every file and its contents, in path order.
+    #[must_use]
+    pub fn files(&self) -> BTreeMap<String, String> {
+        self.lock().clone()
+    }
+
+    /// Locks the files, recovering them if another thread panicked while holding the lock.
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, String>> {
+        self.files.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read(&self, file: &str) -> io::Result<Option<(String, FileStamp)>> {
+        Ok(self.get(file).map(|content| {
+            let stamp = FileStamp {
+                len: content.len() as u64,
+                modified: None,
+                hash: content_hash(&content),
+            };
+            (content, stamp)
+        }))
+    }
+
+    fn unchanged(&self, file: &str, stamp: Option<&FileStamp>) -> io::Result<bool> {
+        Ok(match (self.lock().get(file), stamp) {
+            (Some(content), Some(stamp)) => content_hash(content) == stamp.hash,
+            (None, None) => true,
+            _ => false,
+        })
+    }
+
+    fn commit(&self, changes: &HashMap<String, FileChange>) -> Result<(), PatchError> {
+        let mut files = self.lock();
+        let missing = changes
+            .iter()
+            .find(|(file, change)| **change == FileChange::Delete && !files.contains_key(*file));
+        if let Some((file, _)) = missing {
+            return Err(PatchError::WriteFailed {
+                file: file.clone(),
+                source: io::ErrorKind::NotFound.into(),
+            });
+        }
+        for (file, change) in changes {
+            match change {
+                FileChange::Write(content) => {
+                    files.insert(file.clone(), content.clone());
+                }
+                FileChange::Delete => {
+                    files.remove(file);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/file_system.rs"]
+mod file_system_tests;
@@ -0,0 +1,241 @@
+//! The in-memory state of the files a patch set touches.
+//!
+//! File operations and text edits are applied to this table rather than to disk, so that a
+//! patch set either succeeds as a whole or leaves every file untouched.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ropey::Rope;
+
+use super::file_system::FileSystem;
+use super::inverse::{self, InverseLog};
+use super::line_ending::{normalise_patch, LineEnding};
+use super::stamp::FileStamp;
+use crate::patch::{Operation, Patch, PatchError, PatchStatus};
+
+/// A change a patch set makes to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// Write these contents to the file, creating it if necessary.
+    Write(String),
+    /// Remove the file.
+    Delete,
+}
+
+/// The original contents of files, `None` for a file that did not exist.
+pub(crate) type Originals = BTreeMap<String, Option<String>>;
+
+/// Original and current contents of every file a patch set reads.
+#[derive(Debug)]
+pub(crate) struct FileTable<'a> {
+    /// Where files are read from.
+    file_system: &'a dyn FileSystem,
+    /// Contents before the patch set, `None` if the file did not exist.
+    original: BTreeMap<String, Option<String>>,
+    /// Contents now, `None` if the file does not exist.
+    current: HashMap<String, Option<Rope>>,
+    /// Files created, deleted or moved by file operations.
+    replaced: HashSet<String>,
+    /// Files whose text was edited in place.
+    edited: HashSet<String>,
+    /// How each file was when it was read, `None` if it did not exist.
+    stamps: HashMap<String, Option<FileStamp>>,
+}
+
+/// The result of a patch set, as gathered in a [`FileTable`].
+pub(crate) struct Finished {
+    /// The change to make to each file.
+    pub(crate) changes: HashMap<String, FileChange>,
+    /// The patches that undo the changes.
+    pub(crate) inverse: Vec<Patch>,
+    /// The original contents of each file changed.
+    pub(crate) originals: Originals,
+    /// How each file changed was when it was read.
+    pub(crate) stamps: HashMap<String, Option<FileStamp>>,
+}
+
+impl<'a> FileTable<'a> {
+    /// Creates an empty table of files read from `file_system`.
+    pub(crate) fn new(file_system: &'a dyn FileSystem) -> Self {
+        Self {
+            file_system,
+            original: BTreeMap::new(),
+            current: HashMap::new(),
+            replaced: HashSet::new(),
+            edited: HashSet::new(),
+            stamps: HashMap::new(),
+        }
+    }
+
+    /// Returns the current contents of a file, reading it the first time.
+    fn get(&mut self, file: &str) -> Result<Option<&Rope>, PatchError> {
+        if !self.original.contains_key(file) {
+            let (content, stamp) = match self.file_system.read(file)? {
+                Some((content, stamp)) => (Some(content), Some(stamp)),
+                None => (None, None),
+            };
+            self.current
+                .insert(file.to_string(), content.as_deref().map(Rope::from_str));
+            self.original.insert(file.to_string(), content);
+            self.stamps.insert(file.to_string(), stamp);
+        }
+        Ok(self.current[file].as_ref())
+    }
+
+    /// Returns the current contents of a file for editing in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::FileNotFound`] if the file does not exist, or an I/O error if it
+    /// cannot be read.
+    pub(crate) fn edit(&mut self, file: &str) -> Result<Rope, PatchError> {
+        self.get(file)?
+            .cloned()
+            .ok_or_else(|| PatchError::FileNotFound {
+                file: file.to_string(),
+            })
+    }
+
+    /// Stores the edited contents of a file.
+    pub(crate) fn put(&mut self, file: String, rope: Rope) {
+        self.edited.insert(file.clone());
+        self.current.insert(file, Some(rope));
+    }
+
+    /// Restores a file to its original state, discarding every change made to it.
+    pub(crate) fn revert(&mut self, file: &str) {
+        self.replaced.remove(file);
+        self.edited.remove(file);
+        if let Some(original) = self.original.get(file) {
+            self.current
+                .insert(file.to_string(), original.as_deref().map(Rope::from_str));
+        }
+    }
+
+    /// Sets whether and with what contents a file exists, as a file operation.
+    fn replace(&mut self, file: &str, rope: Option<Rope>) {
+        self.replaced.insert(file.to_string());
+        self.current.insert(file.to_string(), rope);
+    }
+
+    /// Applies a create, delete or rename patch, returning whether it was applied or skipped.
+    ///
+    /// The patch is resolved against the file's current contents, so its expected precondition
+    /// applies to the whole file. An idempotent patch is skipped when the file already has the
+    /// contents it would create, or is already absent from where it would be deleted or moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::FileExists`] if the file to create or the rename destination
+    /// already exists, [`PatchError::FileNotFound`] if the file to delete or move does not, and
+    /// any error from resolving the patch.
+    pub(crate) fn apply_file_operation(
+        &mut self,
+        patch: &Patch,
+        line_ending: LineEnding,
+    ) -> Result<PatchStatus, PatchError> {
+        let file = patch.file.as_str();
+        let existing = self.get(file)?.cloned();
+        let missing = || PatchError::FileNotFound {
+            file: file.to_string(),
+        };
+
+        match &patch.operation {
+            Operation::CreateFile { overwrite } => {
+                let exists = existing.is_some();
+                let mut rope = existing.unwrap_or_default();
+                let patch = match line_ending.terminator(&rope) {
+                    Some(terminator) => normalise_patch(patch, terminator),
+                    None => Cow::Borrowed(patch),
+                };
+                let Some(edit) = patch.resolve(&rope)? else {
+                    return Ok(PatchStatus::Skipped);
+                };
+                if exists && !overwrite {
+                    return Err(PatchError::FileExists {
+                        file: file.to_string(),
+                    });
+                }
+                edit.apply(&mut rope)?;
+                self.replace(file, Some(rope));
+            }
+            Operation::DeleteFile => {
+                let Some(rope) = existing else {
+                    return if patch.idempotent {
+                        Ok(PatchStatus::Skipped)
+                    } else {
+                        Err(missing())
+                    };
+                };
+                patch.resolve(&rope)?;
+                self.replace(file, None);
+            }
+            Operation::RenameFile { to } => {
+                let destination_exists = self.get(to)?.is_some();
+                let Some(rope) = existing else {
+                    return if patch.idempotent && destination_exists {
+                        Ok(PatchStatus::Skipped)
+                    } else {
+                        Err(missing())
+                    };
+                };
+                if destination_exists {
+                    return Err(PatchError::FileExists { file: to.clone() });
+                }
+                patch.resolve(&rope)?;
+                self.replace(file, None);
+                self.replace(to, Some(rope));
+            }
+            _ => unreachable!("text edits are not file operations"),
+        }
+        Ok(PatchStatus::Applied)
+    }
+
+    /// Returns the change to make to each file, the patches that undo them, and the original
+    /// contents and stamp of each file changed.
+    ///
+    /// Files edited only in place are reverted through the edit logs in `logs`. Files created,
+    /// deleted or moved are reverted by restoring their original state as a whole, so a rename
+    /// is undone by recreating the source and deleting the destination.
+    pub(crate) fn finish(mut self, mut logs: HashMap<String, InverseLog>) -> Finished {
+        let mut changes = HashMap::new();
+        let mut patches = Vec::new();
+        let mut originals = BTreeMap::new();
+        let mut stamps = HashMap::new();
+
+        for (file, original) in self.original {
+            let replaced = self.replaced.contains(&file);
+            if !replaced && !self.edited.contains(&file) {
+                continue;
+            }
+            let current = self.current.remove(&file).flatten();
+
+            match (replaced, logs.remove(&file), &current) {
+                (false, Some(log), Some(rope)) => patches.extend(log.into_patches(&file, rope)),
+                _ => patches.extend(inverse::restore_file(
+                    &file,
+                    original.as_deref(),
+                    current.as_ref(),
+                )),
+            }
+
+            let change = match current {
+                Some(rope) => FileChange::Write(rope.to_string()),
+                None if original.is_some() => FileChange::Delete,
+                None => continue,
+            };
+            changes.insert(file.clone(), change);
+            let stamp = self.stamps.remove(&file).flatten();
+            stamps.insert(file.clone(), stamp);
+            originals.insert(file, original);
+        }
+
+        Finished {
+            changes,
+            inverse: patches,
+            originals,
+            stamps,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Recording applied edits so that they can be reverted.
+//!
+//! Each edit is logged against the rope as it stood just before the edit, and the log keeps a
+//! set of disjoint regions of the *current* text alongside the original text each region
+//! replaced. Edits that overlap or touch an existing region are merged into it, so the log always
+//! describes the full difference between the original and the current text, however the edits
+//! were sequenced.
+
+use ropey::Rope;
+
+use crate::patch::{Edit, Expected, Operation, Patch};
+use crate::snip::snippet::replacement::escape_match_template;
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::Target;
+
+/// A region of the current text and the original text it replaced.
+#[derive(Debug, Clone)]
+struct Region {
+    /// Start of the region in the current text (character index).
+    start: usize,
+    /// End of the region in the current text (exclusive).
+    end: usize,
+    /// Original text that occupied the region.
+    original: String,
+}
+
+/// A log of edits applied to one file, from which the inverse patches are built.
+#[derive(Debug, Default)]
+pub(crate) struct InverseLog {
+    /// Disjoint, non-touching regions, sorted by start.
+    regions: Vec<Region>,
+}
+
+impl InverseLog {
+    /// Record an edit that is about to be applied to `rope`.
+    ///
+    /// A batch of non-overlapping edits resolved against the same rope can be recorded against
+    /// it in order of descending position, before any of them is applied.
+    pub(crate) fn record(&mut self, rope: &Rope, edit: &Edit) {
+        let inserted = edit.text.chars().count();
+        let removed = edit.end - edit.start;
+
+        // Regions overlapping or touching the edit are absorbed into one
+        let first = self.regions.partition_point(|r| r.end < edit.start);
+        let last = self.regions.partition_point(|r| r.start <= edit.end);
+        let absorbed: Vec<Region> = self.regions.drain(first..last).collect();
+
+        let start = absorbed
+            .first()
+            .map_or(edit.start, |r| r.start.min(edit.start));
+        let end = absorbed.last().map_or(edit.end, |r| r.end.max(edit.end));
+
+        let mut original = String::new();
+        let mut cursor = start;
+        for region in &absorbed {
+            original.extend(rope.slice(cursor..region.start).chunks());
+            original.push_str(&region.original);
+            cursor = region.end;
+        }
+        // Logging a batch against the rope it was resolved on, the last region can end past that
+        // rope's text, with nothing left to read
+        if cursor < end {
+            original.extend(rope.slice(cursor..end).chunks());
+        }
+
+        // Text after the edit moves by the change in length
+        let new_end = end + inserted - removed;
+        for region in &mut self.regions[first..] {
+            region.start = region.start + inserted - removed;
+            region.end = region.end + inserted - removed;
+        }
+        self.regions.insert(
+            first,
+            Region {
+                start,
+                end: new_end,
+                original,
+            },
+        );
+    }
+
+    /// Build the patches restoring the original text of `file` from its current contents.
+    ///
+    /// Each patch selects its region through character targets and carries the region's current
+    /// text as its expected precondition, so it refuses to apply if the file has since changed.
+    pub(crate) fn into_patches(self, file: &str, rope: &Rope) -> Vec<Patch> {
+        let len = rope.len_chars();
+        self.regions
+            .into_iter()
+            .map(|region| {
+                let current = rope.slice(region.start..region.end).to_string();
+                let (snippet, operation) = char_range_snippet(region.start, region.end, len);
+                let expected = if operation.is_file_level() {
+                    Expected::hash_of(&rope.to_string())
+                } else {
+                    Expected::Text(current)
+                };
+                // The original text must come back as it was, not be expanded as a template
+                let replacement = escape_match_template(&region.original);
+                Patch::new(file.to_string(), snippet, replacement)
+                    .with_operation(operation)
+                    .with_expected(expected)
+            })
+            .collect()
+    }
+}
+
+/// Build the patch restoring a file created, deleted or moved by file operations.
+///
+/// The file's current contents are expected, by hash, to be unchanged. Returns `None` if the file
+/// neither existed before nor exists now.
+pub(crate) fn restore_file(
+    file: &str,
+    original: Option<&str>,
+    current: Option<&Rope>,
+) -> Option<Patch> {
+    let file = file.to_string();
+    match (original, current) {
+        (Some(original), Some(current)) => Some(
+            Patch::new(file, Snippet::All, escape_match_template(original))
+                .with_expected(Expected::hash_of(&current.to_string())),
+        ),
+        (Some(original), None) => Some(
+            Patch::new(file, Snippet::All, original)
+                .with_operation(Operation::CreateFile { overwrite: false }),
+        ),
+        (None, Some(current)) => Some(
+            Patch::new(file, Snippet::All, "")
+                .with_operation(Operation::DeleteFile)
+                .with_expected(Expected::hash_of(&current.to_string())),
+        ),
+        (None, None) => None,
+    }
+}
+
+/// Select the character range `[start, end)` of a rope with `len` characters.
+///
+/// Character targets must lie within the rope, so ranges reaching the end of the rope are
+/// expressed relative to the preceding character, and an insertion point at the very end becomes
+/// an append.
+fn char_range_snippet(start: usize, end: usize, len: usize) -> (Snippet, Operation) {
+    if start == len {
+        (Snippet::All, Operation::Append)
+    } else if end == len && start == 0 {
+        (Snippet::All, Operation::Replace)
+    } else if end == len {
+        let before = Boundary::new(Target::Char(start - 1), BoundaryMode::Exclude);
+        (Snippet::From(before), Operation::Replace)
+    } else {
+        let start = Boundary::new(Target::Char(start), BoundaryMode::Include);
+        let end = Boundary::new(Target::Char(end), BoundaryMode::Exclude);
+        (Snippet::Between { start, end }, Operation::Replace)
+    }
+}
@@ -0,0 +1,152 @@
+//! Detecting and normalising the line endings of replacement text.
+//!
+//! Replacements are usually written with `\n`, so inserting them verbatim into a file that uses
+//! `\r\n` mixes line endings. A [`PatchSet`](crate::PatchSet) instead normalises every
+//! replacement to a single line ending chosen per file.
+
+use std::borrow::Cow;
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+use ropey::Rope;
+
+use crate::patch::{Operation, Patch};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// The line ending replacement text is normalised to when patches are applied.
+///
+/// # Examples
+///
+/// ```
+/// use ropey::Rope;
+/// use textum::LineEnding;
+///
+/// let rope = Rope::from_str("a\r\nb\r\nc\n");
+/// assert_eq!(LineEnding::Preserve.terminator(&rope), Some("\r\n"));
+/// assert_eq!(LineEnding::Lf.terminator(&rope), Some("\n"));
+/// assert_eq!(LineEnding::Preserve.terminator(&Rope::from_str("one line")), None);
+/// assert_eq!(LineEnding::Verbatim.terminator(&rope), None);
+/// ```
+pub enum LineEnding {
+    /// Use the file's dominant line ending. Files without line breaks are left as written.
+    #[default]
+    Preserve,
+    /// Always use `\n`.
+    Lf,
+    /// Always use `\r\n`.
+    Crlf,
+    /// Insert text exactly as written, whatever line endings it or the file uses.
+    ///
+    /// Inverse patch sets use this, so undoing a change restores the original text byte for
+    /// byte.
+    Verbatim,
+}
+
+impl LineEnding {
+    /// Returns the terminator replacement text in `rope` should use, if any.
+    #[must_use]
+    pub fn terminator(self, rope: &Rope) -> Option<&'static str> {
+        match self {
+            Self::Preserve => detect(rope),
+            Self::Lf => Some("\n"),
+            Self::Crlf => Some("\r\n"),
+            Self::Verbatim => None,
+        }
+    }
+}
+
+/// Returns the dominant line ending of a rope, or `None` if it contains no `\n`.
+///
+/// `\r\n` is counted as one terminator. Ties are resolved in favour of `\n`.
+///
+/// # Examples
+///
+/// ```
+/// use ropey::Rope;
+/// use textum::composer::line_ending::detect;
+///
+/// assert_eq!(detect(&Rope::from_str("a\r\nb\r\n")), Some("\r\n"));
+/// assert_eq!(detect(&Rope::from_str("a\r\nb\n")), Some("\n"));
+/// assert_eq!(detect(&Rope::from_str("a")), None);
+/// ```
+#[must_use]
+pub fn detect(rope: &Rope) -> Option<&'static str> {
+    let (mut lf, mut crlf) = (0usize, 0usize);
+    let mut prev = None;
+    for c in rope.chars() {
+        if c == '\n' {
+            if prev == Some('\r') {
+                crlf += 1;
+            } else {
+                lf += 1;
+            }
+        }
+        prev = Some(c);
+    }
+    match (lf, crlf) {
+        (0, 0) => None,
+        (lf, crlf) if crlf > lf => Some("\r\n"),
+        _ => Some("\n"),
+    }
+}
+
+/// Rewrites every `\n` and `\r\n` in `text` as `terminator`.
+///
+/// Lone `\r` characters are left untouched. The text is borrowed if nothing changes.
+///
+/// # Examples
+///
+/// ```
+/// use textum::composer::line_ending::normalise;
+///
+/// assert_eq!(normalise("a\nb\r\n", "\r\n"), "a\r\nb\r\n");
+/// assert_eq!(normalise("a\r\nb\n", "\n"), "a\nb\n");
+/// ```
+#[must_use]
+pub fn normalise<'a>(text: &'a str, terminator: &str) -> Cow<'a, str> {
+    let consistent = text.match_indices('\n').all(|(i, _)| {
+        let crlf = i > 0 && text.as_bytes()[i - 1] == b'\r';
+        crlf == (terminator == "\r\n")
+    });
+    if consistent {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace("\r\n", "\n").replace('\n', terminator))
+}
+
+/// Returns `patch` with its inserted text normalised to `terminator`.
+///
+/// The replacement and any wrapping prefix and suffix are normalised; the patch is borrowed if
+/// they already use the terminator throughout.
+pub(crate) fn normalise_patch<'a>(patch: &'a Patch, terminator: &str) -> Cow<'a, Patch> {
+    let replacement = normalise(&patch.replacement, terminator);
+    let operation = match &patch.operation {
+        Operation::Wrap { prefix, suffix } => {
+            let (p, s) = (normalise(prefix, terminator), normalise(suffix, terminator));
+            if matches!((&p, &s), (Cow::Borrowed(_), Cow::Borrowed(_))) {
+                None
+            } else {
+                Some(Operation::Wrap {
+                    prefix: p.into_owned(),
+                    suffix: s.into_owned(),
+                })
+            }
+        }
+        _ => None,
+    };
+    if matches!(replacement, Cow::Borrowed(_)) && operation.is_none() {
+        return Cow::Borrowed(patch);
+    }
+    let mut owned = patch.clone();
+    owned.replacement = replacement.into_owned();
+    if let Some(operation) = operation {
+        owned.operation = operation;
+    }
+    Cow::Owned(owned)
+}
+
+#[cfg(test)]
+#[path = "../tests/line_endings.rs"]
+mod line_endings;
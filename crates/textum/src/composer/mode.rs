@@ -0,0 +1,23 @@
+//! How the patches for one file are resolved relative to each other.
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// Whether the patches for a file see each other's changes.
+///
+/// In either mode a patch that [depends on](crate::Patch::depends_on) others is resolved only
+/// after they have been applied.
+pub enum ApplyMode {
+    /// Resolve every patch against the same snapshot of the file, then apply all the edits.
+    ///
+    /// Patches cannot target text another patch introduces, and overlapping edits are handled
+    /// according to the set's [`OverlapPolicy`](crate::OverlapPolicy).
+    #[default]
+    Snapshot,
+    /// Resolve and apply the patches one at a time in the order they were added, each against
+    /// the text the previous ones produced.
+    Sequential,
+}
@@ -0,0 +1,254 @@
+//! Deciding what happens when the edits resolved in one batch overlap.
+//!
+//! Two edits overlap when the range one removes intersects the range the other removes, or when
+//! one inserts text strictly inside the range the other removes. Edits that merely touch do not
+//! overlap: an insertion at either end of a replaced range is applied next to it.
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+use crate::patch::error::PatchRef;
+use crate::patch::{Edit, Patch, PatchError};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// How a patch set handles patches whose edits overlap.
+///
+/// Under every policy, overlapping deletions are combined into a single deletion of the text
+/// either removes, since the result does not depend on their order. Under
+/// [`OverlapPolicy::FirstWins`] and [`OverlapPolicy::LastWins`], each deletion is still weighed
+/// against the other edits on its own before they are combined.
+pub enum OverlapPolicy {
+    /// Fail with [`PatchError::OverlappingRanges`], naming both patches.
+    #[default]
+    Error,
+    /// Apply identical edits (same range, same text) once, combine adjacent edits (one ending
+    /// where the next starts) into a single edit, and fail on any other overlap.
+    Merge,
+    /// Keep the patch declared last and skip the earlier patches it overlaps.
+    LastWins,
+    /// Keep the patch declared first and skip the later patches that overlap it.
+    FirstWins,
+}
+
+/// A patch's index in its set, the patch and the edit it resolved to.
+#[derive(Debug)]
+pub(crate) struct Resolved<'a> {
+    /// Position of the patch in its set.
+    pub(crate) index: usize,
+    /// The patch.
+    pub(crate) patch: &'a Patch,
+    /// The edit the patch resolved to.
+    pub(crate) edit: Edit,
+    /// Indices of other patches whose deletions were merged into this edit.
+    pub(crate) absorbed: Vec<usize>,
+}
+
+impl<'a> Resolved<'a> {
+    /// Pairs the patch at `index` with the edit it resolved to.
+    pub(crate) fn new(index: usize, patch: &'a Patch, edit: Edit) -> Self {
+        Self {
+            index,
+            patch,
+            edit,
+            absorbed: Vec::new(),
+        }
+    }
+
+    /// Returns whether this edit removes text and inserts none.
+    fn is_deletion(&self) -> bool {
+        self.edit.text.is_empty() && self.edit.start < self.edit.end
+    }
+}
+
+/// Reduces the edits of a batch to those to apply, in no particular order, according to
+/// `policy`.
+///
+/// Every policy runs in O(n log n) for n edits.
+///
+/// # Errors
+///
+/// Returns [`PatchError::OverlappingRanges`] if edits overlap and the policy does not resolve
+/// the overlap. Overlapping deletions are merged in `resolved` either way, so calling this again
+/// without one of the overlapping edits carries on where it stopped.
+pub(crate) fn resolve_overlaps(
+    file: &str,
+    resolved: &mut Vec<Resolved<'_>>,
+    policy: OverlapPolicy,
+) -> Result<(), PatchError> {
+    match policy {
+        OverlapPolicy::Error => {
+            *resolved = merge_deletions(std::mem::take(resolved));
+            check_disjoint(file, resolved)
+        }
+        OverlapPolicy::Merge => {
+            *resolved = merge_deletions(std::mem::take(resolved));
+            // Sorting brings identical edits together, the earliest declared first
+            resolved.sort_by(|a, b| {
+                (a.edit.start, a.edit.end, &a.edit.text, a.index).cmp(&(
+                    b.edit.start,
+                    b.edit.end,
+                    &b.edit.text,
+                    b.index,
+                ))
+            });
+            resolved.dedup_by(|later, earlier| later.edit == earlier.edit);
+            check_disjoint(file, resolved)?;
+            *resolved = merge_adjacent(std::mem::take(resolved));
+            Ok(())
+        }
+        OverlapPolicy::FirstWins => {
+            *resolved = keep_non_overlapping(std::mem::take(resolved));
+            Ok(())
+        }
+        OverlapPolicy::LastWins => {
+            resolved.reverse();
+            *resolved = keep_non_overlapping(std::mem::take(resolved));
+            Ok(())
+        }
+    }
+}
+
+/// Combines overlapping deletions into one, attributed to the first patch declared.
+///
+/// The result stays in declaration order.
+fn merge_deletions(resolved: Vec<Resolved<'_>>) -> Vec<Resolved<'_>> {
+    let (mut deletions, mut others): (Vec<_>, Vec<_>) =
+        resolved.into_iter().partition(Resolved::is_deletion);
+
+    deletions.sort_by_key(|r| r.edit.start);
+    let mut merged: Vec<Resolved> = Vec::with_capacity(deletions.len());
+    for deletion in deletions {
+        match merged.last_mut() {
+            Some(last) if deletion.edit.start < last.edit.end => {
+                last.edit.end = last.edit.end.max(deletion.edit.end);
+                if deletion.index < last.index {
+                    last.absorbed.push(last.index);
+                    last.index = deletion.index;
+                    last.patch = deletion.patch;
+                } else {
+                    last.absorbed.push(deletion.index);
+                }
+                last.absorbed.extend(deletion.absorbed);
+            }
+            _ => merged.push(deletion),
+        }
+    }
+
+    others.extend(merged);
+    others.sort_by_key(|r| r.index);
+    others
+}
+
+/// Combines each run of disjoint edits that touch end to start into one edit, attributed to the
+/// first of them.
+///
+/// The edits are taken in the order they appear in the output, insertions at a shared position
+/// by descending priority and then declaration order, so the combined text is the text they
+/// would have produced apart.
+fn merge_adjacent(mut resolved: Vec<Resolved<'_>>) -> Vec<Resolved<'_>> {
+    resolved.sort_by_key(|r| (r.edit.start, r.edit.end, Reverse(r.patch.priority), r.index));
+    let mut merged: Vec<Resolved> = Vec::with_capacity(resolved.len());
+    for item in resolved {
+        match merged.last_mut() {
+            Some(last) if last.edit.end == item.edit.start => {
+                last.edit.end = item.edit.end;
+                last.edit.text.push_str(&item.edit.text);
+                last.absorbed.push(item.index);
+                last.absorbed.extend(item.absorbed);
+            }
+            _ => merged.push(item),
+        }
+    }
+    merged
+}
+
+/// Checks that no two edits overlap, sorting them by position.
+///
+/// Sorted by start, then end, an edit overlaps an earlier one exactly when it starts before the
+/// furthest end seen so far: an insertion sorts ahead of a range starting at the same position,
+/// so the two only touch.
+fn check_disjoint(file: &str, resolved: &mut [Resolved<'_>]) -> Result<(), PatchError> {
+    resolved.sort_by_key(|r| (r.edit.start, r.edit.end));
+    let mut furthest: Option<&Resolved> = None;
+    for item in resolved.iter() {
+        match furthest {
+            Some(widest) if item.edit.start < widest.edit.end => {
+                return Err(overlap_error(file, widest, item));
+            }
+            Some(widest) if item.edit.end <= widest.edit.end => {}
+            _ => furthest = Some(item),
+        }
+    }
+    Ok(())
+}
+
+/// Keeps each edit that does not overlap an edit already kept, in the order given, then combines
+/// the overlapping deletions kept.
+///
+/// A deletion is only checked against the other edits, never against deletions, so each is kept
+/// or skipped by its own position in the order. The kept edits and the spans the kept deletions
+/// cover never overlap, so ordered by start, then end, their ends never decrease: a new edit
+/// `[start, end)` overlaps one of them exactly when the last starting before `end` ends after
+/// `start`.
+fn keep_non_overlapping(resolved: Vec<Resolved<'_>>) -> Vec<Resolved<'_>> {
+    let mut kept: BTreeMap<(usize, usize, usize), Resolved> = BTreeMap::new();
+    let mut deletions = Vec::new();
+    // The spans covered by the deletions kept, from start to end
+    let mut deleted: BTreeMap<usize, usize> = BTreeMap::new();
+    for item in resolved {
+        let (start, end) = (item.edit.start, item.edit.end);
+        let blocked = kept
+            .range(..(end, 0, 0))
+            .next_back()
+            .is_some_and(|(_, last)| last.edit.end > start);
+        if blocked {
+            continue;
+        }
+        if item.is_deletion() {
+            let overlapping: Vec<(usize, usize)> = deleted
+                .range(..end)
+                .rev()
+                .map(|(&from, &to)| (from, to))
+                .take_while(|&(_, to)| to > start)
+                .collect();
+            let mut span = (start, end);
+            for (from, to) in overlapping {
+                deleted.remove(&from);
+                span = (span.0.min(from), span.1.max(to));
+            }
+            deleted.insert(span.0, span.1);
+            deletions.push(item);
+        } else if deleted
+            .range(..end)
+            .next_back()
+            .is_none_or(|(_, &to)| to <= start)
+        {
+            kept.insert((start, end, item.index), item);
+        }
+    }
+    let mut kept: Vec<Resolved> = kept.into_values().collect();
+    kept.extend(merge_deletions(deletions));
+    kept
+}
+
+/// Builds the error reporting two overlapping edits, the earlier-declared first.
+fn overlap_error(file: &str, first: &Resolved, second: &Resolved) -> PatchError {
+    let (first, second) = if first.index <= second.index {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    PatchError::OverlappingRanges {
+        file: file.to_string(),
+        range1: (first.edit.start, first.edit.end),
+        range2: (second.edit.start, second.edit.end),
+        patches: Box::new([
+            PatchRef::new(first.index, first.patch),
+            PatchRef::new(second.index, second.patch),
+        ]),
+    }
+}
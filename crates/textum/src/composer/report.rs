@@ -0,0 +1,157 @@
+//! Reporting what each patch in a set did when it was applied.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+use super::{FileChange, FileStamp, FileSystem, PatchSet};
+use crate::patch::{Patch, PatchError, PatchStatus};
+use crate::snip::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+/// What one patch in a set did.
+pub struct PatchReport {
+    /// Position of the patch in its set, counting from zero.
+    pub index: usize,
+    /// The patch's id, if it has one.
+    pub id: Option<String>,
+    /// The file the patch applies to.
+    pub file: String,
+    /// Whether the patch changed the file.
+    pub status: PatchStatus,
+    /// The range the patch resolved to, in the text it was resolved against, and the text it
+    /// removed.
+    ///
+    /// `None` for file operations, and for idempotent patches skipped because their change was
+    /// already present.
+    pub span: Option<Span>,
+    /// The text the patch inserted, after template expansion, re-indentation and line ending
+    /// normalisation.
+    pub inserted: String,
+    /// Time spent resolving the patch, in microseconds.
+    pub elapsed_micros: u64,
+    /// Why the patch failed, if its status is [`PatchStatus::Failed`].
+    pub error: Option<String>,
+}
+
+impl PatchReport {
+    /// Starts the report for the patch at `index` in its set.
+    pub(crate) fn new(index: usize, patch: &Patch, status: PatchStatus) -> Self {
+        Self {
+            index,
+            id: patch.id.clone(),
+            file: patch.file.clone(),
+            status,
+            span: None,
+            inserted: String::new(),
+            elapsed_micros: 0,
+            error: None,
+        }
+    }
+
+    /// Sets the time spent resolving the patch.
+    pub(crate) fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed_micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Returns the text the patch removed.
+    #[must_use]
+    pub fn removed(&self) -> &str {
+        self.span.as_ref().map_or("", |span| span.text.as_str())
+    }
+
+    /// Returns the time spent resolving the patch.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed_micros)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+/// What every patch in a set did, in the order the patches were added.
+pub struct ApplyReport {
+    /// One report per patch.
+    pub patches: Vec<PatchReport>,
+}
+
+impl ApplyReport {
+    /// Returns the reports of patches with the given status.
+    pub fn with_status(&self, status: PatchStatus) -> impl Iterator<Item = &PatchReport> {
+        self.patches.iter().filter(move |p| p.status == status)
+    }
+
+    /// Serialise this report as JSON.
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        facet_json::to_string(self)
+    }
+}
+
+/// Everything produced by applying a patch set.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The change to make to each file.
+    pub changes: HashMap<String, FileChange>,
+    /// The patch set that undoes the changes.
+    pub inverse: PatchSet,
+    /// What each patch did.
+    pub report: ApplyReport,
+    /// The errors of the patches that failed, in the order of the patches, when the set's
+    /// [`Atomicity`](crate::Atomicity) lets it carry on past them.
+    pub failures: Vec<PatchError>,
+    /// How each file in `changes` was when it was read, `None` if it did not exist.
+    pub stamps: HashMap<String, Option<FileStamp>>,
+    /// Where the files were read from.
+    pub(crate) file_system: Arc<dyn FileSystem>,
+}
+
+impl Outcome {
+    /// Checks that no file to change has changed in the set's [`FileSystem`] since the patch
+    /// set read it.
+    ///
+    /// [`PatchSet::write`] does this itself. Callers writing the changes some other way should
+    /// call it just before they write, so as not to overwrite changes another program saved
+    /// in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::FileChanged`] for the first file, in path order, that was
+    /// modified, created or deleted since it was read, or an I/O error if one cannot be
+    /// inspected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Patch, PatchError, PatchSet};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("a.txt").to_string_lossy().to_string();
+    /// std::fs::write(&path, "hello world\n").unwrap();
+    ///
+    /// let mut set = PatchSet::new();
+    /// set.add(Patch::from_literal_target(path.clone(), "world", BoundaryMode::Include, "rust"));
+    /// let outcome = set.apply().unwrap();
+    /// assert!(outcome.verify().is_ok());
+    ///
+    /// std::fs::write(&path, "hello there, world\n").unwrap();
+    /// assert!(matches!(outcome.verify(), Err(PatchError::FileChanged { .. })));
+    /// ```
+    pub fn verify(&self) -> Result<(), PatchError> {
+        let mut files: Vec<&String> = self.changes.keys().collect();
+        files.sort();
+        for file in files {
+            let stamp = self.stamps.get(file).and_then(Option::as_ref);
+            if !self.file_system.unchanged(file, stamp)? {
+                return Err(PatchError::FileChanged { file: file.clone() });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! Noticing when a file changes on disk between being read and being written.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::time::SystemTime;
+
+use crate::patch::content_hash;
+
+/// How a file was when a patch set read it.
+///
+/// Compared against the file again before its changes are written, so that changes saved by
+/// another program in the meantime are not overwritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStamp {
+    /// The file's length in bytes.
+    pub len: u64,
+    /// When the file was last modified, where the platform records it.
+    pub modified: Option<SystemTime>,
+    /// SHA-256 of the file's contents, as given by [`content_hash`].
+    pub hash: String,
+}
+
+impl FileStamp {
+    /// Reads a file, returning its contents and stamp, or `None` if it does not exist.
+    pub(crate) fn read(file: &str) -> io::Result<Option<(String, Self)>> {
+        let mut handle = match File::open(file) {
+            Ok(handle) => handle,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // Taken from the open handle, so the stamp describes the file as it is read
+        let metadata = handle.metadata()?;
+        let mut content = String::new();
+        handle.read_to_string(&mut content)?;
+        let stamp = Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            hash: content_hash(&content),
+        };
+        Ok(Some((content, stamp)))
+    }
+
+    /// Returns whether the file at `file` still matches this stamp.
+    ///
+    /// The contents are only hashed when the length matches but the modification time does not
+    /// (or is not recorded), so a file touched without being changed still matches.
+    pub(crate) fn matches(&self, file: &str, metadata: &fs::Metadata) -> io::Result<bool> {
+        if metadata.len() != self.len {
+            return Ok(false);
+        }
+        if self.modified.is_some() && metadata.modified().ok() == self.modified {
+            return Ok(true);
+        }
+        match fs::read_to_string(file) {
+            Ok(content) => Ok(content_hash(&content) == self.hash),
+            // The file was read as text, so it no longer being text is a change
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/stamp.rs"]
+mod stamp_tests;
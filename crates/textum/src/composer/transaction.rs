@@ -0,0 +1,233 @@
+//! Writing the changes of a patch set to disk all-or-nothing.
+//!
+//! Every new content is first written to a temporary file beside its target and flushed to
+//! disk. Only once all of them are written is each renamed over its target, which replaces the
+//! file atomically. The file it replaces stays reachable through a backup link until the whole
+//! set is in place, so that a failure part way through can put every file back.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::FileChange;
+use crate::patch::PatchError;
+
+/// Makes `changes` on disk, leaving every file as it was if any of them cannot be made.
+///
+/// Files are written in path order, and files to delete are removed once every write is in
+/// place.
+///
+/// # Errors
+///
+/// Returns [`PatchError::WriteFailed`] naming the file that could not be written, moved or
+/// removed.
+pub(crate) fn commit(changes: &HashMap<String, FileChange>) -> Result<(), PatchError> {
+    let ordered: BTreeMap<&str, &FileChange> =
+        changes.iter().map(|(file, c)| (file.as_str(), c)).collect();
+    let mut transaction = Transaction::default();
+    match transaction.run(&ordered) {
+        Ok(()) => {
+            transaction.finish();
+            Ok(())
+        }
+        Err(e) => {
+            transaction.roll_back();
+            Err(e)
+        }
+    }
+}
+
+/// How to put back a file the transaction changed.
+enum Undo {
+    /// The file did not exist: remove it.
+    Remove(PathBuf),
+    /// The original file is linked at `backup`: move it back to `target`.
+    Restore {
+        /// The original file.
+        backup: PathBuf,
+        /// Where it was.
+        target: PathBuf,
+    },
+}
+
+/// The files a commit has touched so far.
+#[derive(Default)]
+struct Transaction {
+    /// Temporary files written and not yet renamed into place, with their targets.
+    staged: Vec<(PathBuf, PathBuf)>,
+    /// Every change made to a target so far, in order.
+    done: Vec<Undo>,
+    /// Directories created for new files, outermost first.
+    created_dirs: Vec<PathBuf>,
+}
+
+impl Transaction {
+    /// Stages every write, then moves the writes into place and the deletions aside.
+    fn run(&mut self, changes: &BTreeMap<&str, &FileChange>) -> Result<(), PatchError> {
+        for (file, change) in changes {
+            if let FileChange::Write(content) = change {
+                self.stage(Path::new(file), content)
+                    .map_err(|e| write_failed(file, e))?;
+            }
+        }
+        // Taken from the back, so the temporary files not yet renamed stay staged
+        self.staged.reverse();
+        while let Some((temp, target)) = self.staged.pop() {
+            let file = target.to_string_lossy().to_string();
+            match replace(&temp, &target) {
+                Ok(undo) => self.done.push(undo),
+                Err(e) => {
+                    let _ = fs::remove_file(&temp);
+                    return Err(write_failed(&file, e));
+                }
+            }
+        }
+        for (file, change) in changes {
+            if let FileChange::Delete = change {
+                let target = PathBuf::from(file);
+                let backup = backup_path(&target);
+                fs::rename(&target, &backup).map_err(|e| write_failed(file, e))?;
+                self.done.push(Undo::Restore { backup, target });
+            }
+        }
+        for undo in &self.done {
+            let target = match undo {
+                Undo::Remove(target) | Undo::Restore { target, .. } => target,
+            };
+            sync_parent(target);
+        }
+        Ok(())
+    }
+
+    /// Writes `content` to a temporary file beside `target` and flushes it to disk.
+    ///
+    /// A symlinked target is resolved first, so the file it points to is replaced and the link
+    /// is kept.
+    fn stage(&mut self, target: &Path, content: &str) -> io::Result<()> {
+        let target = resolve(target)?;
+        let target = target.as_path();
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            let missing = parent.ancestors().take_while(|dir| !dir.exists()).last();
+            if let Some(outermost) = missing {
+                fs::create_dir_all(parent)?;
+                self.created_dirs.push(outermost.to_path_buf());
+            }
+        }
+        let temp = sibling(target, "tmp");
+        self.staged.push((temp.clone(), target.to_path_buf()));
+        let mut handle = File::create(&temp)?;
+        handle.write_all(content.as_bytes())?;
+        handle.sync_all()
+    }
+
+    /// Removes the backups of the files replaced, once every change is in place.
+    fn finish(self) {
+        for undo in self.done {
+            if let Undo::Restore { backup, .. } = undo {
+                let _ = fs::remove_file(backup);
+            }
+        }
+    }
+
+    /// Puts back every file changed so far, most recent first, and removes what was created.
+    ///
+    /// Rolling back is best effort: a step that fails does not stop the others.
+    fn roll_back(self) {
+        for (temp, _) in self.staged {
+            let _ = fs::remove_file(temp);
+        }
+        for undo in self.done.into_iter().rev() {
+            let _ = match undo {
+                Undo::Remove(target) => fs::remove_file(target),
+                Undo::Restore { backup, target } => fs::rename(backup, target),
+            };
+        }
+        for dir in self.created_dirs.into_iter().rev() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Returns the file a symlink at `target` points to, or `target` itself if it is not a link.
+fn resolve(target: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(target) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(target),
+        _ => Ok(target.to_path_buf()),
+    }
+}
+
+/// Renames `temp` over `target`, first giving it the permissions of any file it replaces and
+/// linking that file at its backup path.
+fn replace(temp: &Path, target: &Path) -> io::Result<Undo> {
+    let undo = match fs::symlink_metadata(target) {
+        Ok(metadata) => {
+            fs::set_permissions(temp, metadata.permissions())?;
+            let backup = backup_path(target);
+            link_or_copy(target, &backup)?;
+            Undo::Restore {
+                backup,
+                target: target.to_path_buf(),
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Undo::Remove(target.to_path_buf()),
+        Err(e) => return Err(e),
+    };
+    if let Err(e) = fs::rename(temp, target) {
+        if let Undo::Restore { backup, .. } = undo {
+            let _ = fs::remove_file(backup);
+        }
+        return Err(e);
+    }
+    Ok(undo)
+}
+
+/// Returns a hidden path beside `target` for a file of the given kind, unique to this process.
+fn sibling(target: &Path, kind: &str) -> PathBuf {
+    let name = target
+        .file_name()
+        .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+    target.with_file_name(format!(".{name}.textum-{}.{kind}", std::process::id()))
+}
+
+/// Returns the path the original of `target` is kept at until the commit completes.
+fn backup_path(target: &Path) -> PathBuf {
+    sibling(target, "orig")
+}
+
+/// Makes `backup` refer to the file at `target`, copying it where hard links are unsupported.
+fn link_or_copy(target: &Path, backup: &Path) -> io::Result<()> {
+    if fs::hard_link(target, backup).is_err() {
+        fs::copy(target, backup)?;
+    }
+    Ok(())
+}
+
+/// Flushes the directory entry of a renamed file to disk, where the platform allows it.
+fn sync_parent(target: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = target.parent() {
+        let dir = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if let Ok(handle) = File::open(dir) {
+            let _ = handle.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = target;
+}
+
+/// Wraps an I/O error with the file it occurred on.
+fn write_failed(file: &str, source: io::Error) -> PatchError {
+    PatchError::WriteFailed {
+        file: file.to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/transaction.rs"]
+mod transaction_tests;
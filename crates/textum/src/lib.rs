@@ -11,12 +11,14 @@
 //! ## Patches
 //!
 //! A `Patch` specifies a file, a `Snippet` defining the target range, and replacement text.
+//! Its `Operation` says how the text is used: replacing the range, inserting before or after
+//! it, deleting or wrapping it, or prepending/appending to the whole file.
 //! Patches compose through `PatchSet`, which handles resolution, validation, and application.
 //!
 //! ## Snippets
 //!
 //! Snippets define text ranges through:
-//! - **Targets**: What to match (Literal, Pattern, Line, Char, Position)
+//! - **Targets**: What to match (Literal, `LiteralWith`, Pattern, Line, Char, Position)
 //! - **Boundaries**: How to treat matches (Include, Exclude, Extend)
 //! - **Modes**: Range selection (At, From, To, Between, All)
 //!
@@ -82,18 +84,32 @@
 //! );
 //! let snippet = Snippet::Between { start, end };
 //!
-//! let patch = Patch {
-//!     file: "test.txt".to_string(),
-//!     snippet,
-//!     replacement: "new".to_string(),
-//!     #[cfg(feature = "symbol_path")]
-//!     symbol_path: None,
-//! };
+//! let patch = Patch::new("test.txt".to_string(), snippet, "new");
 //!
 //! patch.apply(&mut rope).unwrap();
 //! assert_eq!(rope.to_string(), "<!-- start -->new<!-- end -->");
 //! ```
 //!
+//! ## Insertions and Wrapping
+//!
+//! A patch's `operation` states what to do with the resolved range, so insertions need no
+//! boundary-mode tricks:
+//!
+//! ```
+//! use textum::{BoundaryMode, Operation, Patch};
+//! use ropey::Rope;
+//!
+//! let mut rope = Rope::from_str("let x = value;");
+//! let patch = Patch::from_literal_target("main.rs".to_string(), "value", BoundaryMode::Include, "")
+//!     .with_operation(Operation::Wrap {
+//!         prefix: "Some(".to_string(),
+//!         suffix: ")".to_string(),
+//!     });
+//!
+//! patch.apply(&mut rope).unwrap();
+//! assert_eq!(rope.to_string(), "let x = Some(value);");
+//! ```
+//!
 //! ## Composing Multiple Patches
 //!
 //! ```
@@ -163,8 +179,15 @@ pub mod composer;
 pub mod patch;
 pub mod snip;
 
-pub use composer::PatchSet;
-pub use patch::{Patch, PatchError};
-pub use snip::snippet::boundary::{Boundary, BoundaryMode};
+pub use composer::{
+    ApplyMode, ApplyReport, Atomicity, Backup, FileChange, FileStamp, FileSystem, Journal,
+    JournalEntry, JournalRun, LineEnding, MemoryFileSystem, Outcome, OverlapPolicy, PatchReport,
+    PatchSet, StdFileSystem,
+};
+pub use patch::{content_hash, Edit, Expected, Operation, Patch, PatchError, PatchStatus};
+pub use snip::snippet::boundary::{Boundary, BoundaryMode, MatchCount};
 pub use snip::snippet::{Snippet, SnippetError, SnippetResolution};
-pub use snip::target::Target;
+pub use snip::span::{Location, Span};
+#[cfg(feature = "normalization")]
+pub use snip::target::Normalization;
+pub use snip::target::{LiteralOptions, Target};
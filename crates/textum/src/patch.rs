@@ -7,11 +7,25 @@
 #[cfg(feature = "facet")]
 use facet::Facet;
 use ropey::Rope;
+use std::borrow::Cow;
 
+pub mod edit;
 pub mod error;
+pub mod expected;
+pub mod indent;
+pub mod operation;
+pub mod status;
+pub use edit::Edit;
 pub use error::PatchError;
+pub use expected::{content_hash, Expected};
+pub use operation::Operation;
+pub use status::PatchStatus;
 
-use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::snippet::replacement::{
+    expand_match_template, has_match_placeholder, MATCH_PLACEHOLDER,
+};
+use crate::snip::snippet::{Boundary, BoundaryError, BoundaryMode, Snippet, SnippetError};
+use crate::snip::target::error::TargetError;
 use crate::snip::target::Target;
 
 /// A single atomic patch operation on a file.
@@ -51,17 +65,75 @@ use crate::snip::target::Target;
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Patch {
+    /// Identifier of this patch, cited in errors and referenced by [`Patch::depends_on`].
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub id: Option<String>,
+
+    /// Human-readable description of what this patch does.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub description: Option<String>,
+
+    /// Ids of patches that must be applied before this one.
+    ///
+    /// In a [`PatchSet`](crate::PatchSet), a patch with dependencies is resolved against the text
+    /// its dependencies produced, rather than against the original file.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub depends_on: Vec<String>,
+
     /// File path this patch applies to.
     pub file: String,
 
     /// Snippet defining the target range for this patch.
+    ///
+    /// Defaults to [`Snippet::All`], which suits operations that do not resolve a snippet.
+    #[cfg_attr(feature = "facet", facet(default))]
     pub snippet: Snippet,
 
     /// Replacement text to insert at the resolved range.
     ///
-    /// Empty string performs deletion of the resolved range.
+    /// Empty string performs deletion of the resolved range. How the text is placed relative to
+    /// the range is governed by [`Patch::operation`]. The text is a template: `{{match}}` stands
+    /// for the text of the resolved range (the whole file for file-level operations), and
+    /// `\{{match}}` for a literal `{{match}}`; see
+    /// [`expand_match_template`](crate::snip::snippet::replacement::expand_match_template). The
+    /// contents of an [`Operation::CreateFile`] have nothing to match and are written as given.
+    #[cfg_attr(feature = "facet", facet(default))]
     pub replacement: String,
 
+    /// What to do with the resolved range. Defaults to [`Operation::Replace`].
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub operation: Operation,
+
+    /// Original text the resolved range must contain for the patch to apply.
+    ///
+    /// For file-level operations ([`Operation::Prepend`], [`Operation::Append`] and the file
+    /// operations) the whole file is checked.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub expected: Option<Expected>,
+
+    /// Skip the patch instead of re-applying it when its change is already present.
+    ///
+    /// An idempotent patch is considered applied when its inserted text already sits where it
+    /// would be inserted, or when its target no longer occurs but the replacement text does.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub idempotent: bool,
+
+    /// Re-indent the replacement to the indentation of the resolved range's first line.
+    ///
+    /// The replacement's common leading whitespace is removed and its lines are indented to
+    /// match the patched site, preserving their relative indentation. See
+    /// [`indent::reindent`].
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub reindent: bool,
+
+    /// Placement of this patch's text among other insertions at the same position.
+    ///
+    /// When several patches in a [`PatchSet`](crate::PatchSet) insert text at the same char
+    /// index, the text of higher-priority patches comes first, and patches of equal priority
+    /// keep their declaration order. Defaults to 0.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub priority: i32,
+
     /// Optional symbol path for robust positioning (non-functional, reserved for future use).
     #[cfg_attr(feature = "facet", facet(default))]
     #[cfg(feature = "symbol_path")]
@@ -69,11 +141,314 @@ pub struct Patch {
 }
 
 impl Patch {
+    /// Create a patch replacing the range selected by `snippet` with `replacement`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{Patch, Snippet};
+    ///
+    /// let patch = Patch::new("notes.txt".to_string(), Snippet::All, "fresh contents\n");
+    /// ```
+    #[must_use]
+    pub fn new(file: String, snippet: Snippet, replacement: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            description: None,
+            depends_on: Vec::new(),
+            file,
+            snippet,
+            replacement: replacement.into(),
+            operation: Operation::Replace,
+            expected: None,
+            idempotent: false,
+            reindent: false,
+            priority: 0,
+            #[cfg(feature = "symbol_path")]
+            symbol_path: None,
+        }
+    }
+
+    /// Set the identifier of this patch.
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the description of this patch.
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Declare the ids of patches that must be applied before this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Patch};
+    ///
+    /// let rename = Patch::from_literal_target("a.rs".to_string(), "foo", BoundaryMode::Include, "bar")
+    ///     .with_id("rename");
+    /// let call = Patch::from_literal_target("a.rs".to_string(), "bar()", BoundaryMode::Include, "bar(1)")
+    ///     .with_depends_on(["rename"]);
+    /// assert_eq!(call.depends_on, ["rename"]);
+    /// ```
+    #[must_use]
+    pub fn with_depends_on(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.depends_on = ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the operation this patch performs on its resolved range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{Operation, Patch, Snippet};
+    ///
+    /// let mut rope = Rope::from_str("body\n");
+    /// let patch = Patch::new("notes.txt".to_string(), Snippet::All, "header\n")
+    ///     .with_operation(Operation::Prepend);
+    /// patch.apply(&mut rope).unwrap();
+    /// assert_eq!(rope.to_string(), "header\nbody\n");
+    /// ```
+    #[must_use]
+    pub fn with_operation(mut self, operation: Operation) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// Require the resolved range to contain the given original text before applying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Expected, Patch, PatchError, Target};
+    ///
+    /// let mut rope = Rope::from_str("timeout = 30\n");
+    /// let patch = Patch::from_line_range("conf.toml".to_string(), 0, 1, "timeout = 60\n")
+    ///     .with_expected(Expected::Text("timeout = 10\n".to_string()));
+    ///
+    /// assert!(matches!(patch.apply(&mut rope), Err(PatchError::ExpectedMismatch { .. })));
+    /// assert_eq!(rope.to_string(), "timeout = 30\n");
+    /// ```
+    #[must_use]
+    pub fn with_expected(mut self, expected: Expected) -> Self {
+        self.expected = Some(expected);
+        self
+    }
+
+    /// Mark this patch as idempotent, so that re-running it is a no-op.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Operation, Patch, PatchStatus};
+    ///
+    /// let mut rope = Rope::from_str("fn main() {}\n");
+    /// let patch = Patch::from_literal_target("main.rs".to_string(), "fn main", BoundaryMode::Include, "// entry\n")
+    ///     .with_operation(Operation::InsertBefore)
+    ///     .idempotent();
+    ///
+    /// assert_eq!(patch.apply(&mut rope).unwrap(), PatchStatus::Applied);
+    /// assert_eq!(patch.apply(&mut rope).unwrap(), PatchStatus::Skipped);
+    /// assert_eq!(rope.to_string(), "// entry\nfn main() {}\n");
+    /// ```
+    #[must_use]
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    /// Re-indent the replacement to match the site it is inserted at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Operation, Patch};
+    ///
+    /// let mut rope = Rope::from_str("def f():\n    return 1\n");
+    /// let patch = Patch::from_literal_target(
+    ///     "f.py".to_string(),
+    ///     "return 1",
+    ///     BoundaryMode::Include,
+    ///     "if debug:\n    log()\n",
+    /// )
+    /// .with_operation(Operation::InsertBefore)
+    /// .reindent();
+    ///
+    /// patch.apply(&mut rope).unwrap();
+    /// assert_eq!(rope.to_string(), "def f():\n    if debug:\n        log()\n    return 1\n");
+    /// ```
+    #[must_use]
+    pub fn reindent(mut self) -> Self {
+        self.reindent = true;
+        self
+    }
+
+    /// Set the priority placing this patch's text among insertions at the same position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{BoundaryMode, Operation, Patch, PatchSet};
+    /// # let dir = tempfile::TempDir::new().unwrap();
+    /// # let file = dir.path().join("lib.rs").to_string_lossy().to_string();
+    /// # std::fs::write(&file, "fn f() {}\n").unwrap();
+    ///
+    /// let insert = |text: &str| {
+    ///     Patch::from_literal_target(file.clone(), "fn f", BoundaryMode::Include, text)
+    ///         .with_operation(Operation::InsertBefore)
+    /// };
+    /// let mut set = PatchSet::new();
+    /// set.add(insert("// second\n"));
+    /// set.add(insert("// first\n").with_priority(1));
+    ///
+    /// let results = set.apply_to_files().unwrap();
+    /// assert_eq!(results[&file], "// first\n// second\nfn f() {}\n");
+    /// ```
+    #[must_use]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Resolve this patch against a rope into the concrete edit it would make.
+    ///
+    /// The snippet is resolved to a character range (except for file-level operations, which
+    /// target the start or end of the rope), the `expected` precondition is checked against
+    /// that range, any `{{match}}` placeholders in the replacement are expanded to its text (and
+    /// the result re-indented if requested), and the operation then determines which range is
+    /// removed and what text is inserted.
+    ///
+    /// Returns `None` if the patch is idempotent and already applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PatchError` if the snippet cannot be resolved, if the resolved
+    /// range extends beyond the rope's character count, or if the range does not contain the
+    /// expected original text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{BoundaryMode, Edit, Operation, Patch};
+    ///
+    /// let rope = Rope::from_str("let x = 1;");
+    /// let patch = Patch::from_literal_target("a.rs".to_string(), "1", BoundaryMode::Include, "")
+    ///     .with_operation(Operation::Wrap { prefix: "(".into(), suffix: ")".into() });
+    ///
+    /// assert_eq!(patch.resolve(&rope).unwrap(), Some(Edit::new(8, 9, "(1)")));
+    /// ```
+    pub fn resolve(&self, rope: &Rope) -> Result<Option<Edit>, PatchError> {
+        let (start, end) = if self.operation.is_file_level() {
+            (0, rope.len_chars())
+        } else {
+            let resolution = match self.snippet.resolve(rope) {
+                Ok(resolution) => resolution,
+                Err(err) if self.idempotent && is_not_found(&err) && self.target_consumed(rope) => {
+                    return Ok(None);
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if resolution.end > rope.len_chars() {
+                return Err(PatchError::RangeOutOfBounds);
+            }
+            (resolution.start, resolution.end)
+        };
+
+        let (edit_start, edit_end) = match self.operation {
+            Operation::Prepend => (0, 0),
+            Operation::Append => (end, end),
+            _ => (start, end),
+        };
+
+        // Escaped placeholders need expanding too, even where nothing refers to the match
+        let template = !matches!(self.operation, Operation::CreateFile { .. });
+        let mut replacement = if template && self.replacement.contains(MATCH_PLACEHOLDER) {
+            let matched = rope.slice(start..end).to_string();
+            Cow::Owned(expand_match_template(&self.replacement, &matched))
+        } else {
+            Cow::Borrowed(self.replacement.as_str())
+        };
+        if self.reindent && !self.operation.is_file_level() {
+            // The text inserted before a match is followed by the match itself
+            let resumes = match self.operation {
+                Operation::InsertBefore => start,
+                _ => end,
+            };
+            replacement = Cow::Owned(indent::reindent(
+                &replacement,
+                rope,
+                start,
+                edit_start..resumes,
+            ));
+        }
+
+        if self.idempotent
+            && self
+                .operation
+                .is_applied(rope, edit_start, edit_end, &replacement)
+        {
+            return Ok(None);
+        }
+
+        if let Some(expected) = &self.expected {
+            let actual = rope.slice(start..end).to_string();
+            if !expected.matches(&actual) {
+                return Err(PatchError::ExpectedMismatch {
+                    file: self.file.clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+
+        Ok(Some(self.operation.edit(
+            rope,
+            edit_start,
+            edit_end,
+            &replacement,
+        )))
+    }
+
+    /// Returns whether the absence of this patch's target means its change was already made.
+    ///
+    /// A deleted target is gone once deleted; a replaced or anchored target may be gone once its
+    /// replacement text is present in the rope.
+    fn target_consumed(&self, rope: &Rope) -> bool {
+        match self.operation {
+            Operation::Delete => true,
+            // Without a match, a template's expansion is unknown
+            Operation::Replace | Operation::InsertBefore | Operation::InsertAfter => {
+                !self.replacement.is_empty()
+                    && !has_match_placeholder(&self.replacement)
+                    && rope.to_string().contains(&self.replacement)
+            }
+            Operation::Wrap { .. }
+            | Operation::Prepend
+            | Operation::Append
+            | Operation::CreateFile { .. }
+            | Operation::DeleteFile
+            | Operation::RenameFile { .. } => false,
+        }
+    }
+
     /// Apply this patch to a rope in-place.
     ///
-    /// The rope is modified by resolving the snippet to a character range, then
-    /// removing that range and inserting the replacement text. Changes are applied
-    /// atomically - if the patch cannot be applied, the rope is left unchanged.
+    /// The rope is modified by resolving the patch to an [`Edit`], then removing the edit's
+    /// range and inserting its text. Changes are applied atomically - if the patch cannot be
+    /// applied, the rope is left unchanged. An idempotent patch that is already applied leaves
+    /// the rope unchanged and reports [`PatchStatus::Skipped`]; a patch whose range already holds
+    /// its replacement reports [`PatchStatus::NoOp`].
     ///
     /// # Errors
     ///
@@ -97,22 +472,19 @@ impl Patch {
     /// patch.apply(&mut rope).unwrap();
     /// assert_eq!(rope.to_string(), "hello rust");
     /// ```
-    pub fn apply(&self, rope: &mut Rope) -> Result<(), PatchError> {
-        let resolution = self.snippet.resolve(rope)?;
-
-        if resolution.end > rope.len_chars() {
-            return Err(PatchError::RangeOutOfBounds);
-        }
-
-        // Remove the range
-        if resolution.start < resolution.end {
-            rope.remove(resolution.start..resolution.end);
+    pub fn apply(&self, rope: &mut Rope) -> Result<PatchStatus, PatchError> {
+        match self.resolve(rope)? {
+            Some(edit) => {
+                let status = if edit.is_no_op(rope) {
+                    PatchStatus::NoOp
+                } else {
+                    PatchStatus::Applied
+                };
+                edit.apply(rope)?;
+                Ok(status)
+            }
+            None => Ok(PatchStatus::Skipped),
         }
-
-        // Insert replacement
-        rope.insert(resolution.start, &self.replacement);
-
-        Ok(())
     }
 
     /// Create a patch from a literal string target.
@@ -149,13 +521,7 @@ impl Patch {
         let target = Target::Literal(needle.to_string());
         let boundary = Boundary::new(target, mode);
         let snippet = Snippet::At(boundary);
-        Self {
-            file,
-            snippet,
-            replacement: replacement.into(),
-            #[cfg(feature = "symbol_path")]
-            symbol_path: None,
-        }
+        Self::new(file, snippet, replacement)
     }
 
     /// Create a patch from a line range.
@@ -193,13 +559,7 @@ impl Patch {
         let start = Boundary::new(Target::Line(start_line), BoundaryMode::Include);
         let end = Boundary::new(Target::Line(end_line), BoundaryMode::Exclude);
         let snippet = Snippet::Between { start, end };
-        Self {
-            file,
-            snippet,
-            replacement: replacement.into(),
-            #[cfg(feature = "symbol_path")]
-            symbol_path: None,
-        }
+        Self::new(file, snippet, replacement)
     }
 
     /// Create a patch from line-based positions.
@@ -257,13 +617,26 @@ impl Patch {
         let start = Boundary::new(start_target, BoundaryMode::Include);
         let end = Boundary::new(end_target, BoundaryMode::Exclude);
         let snippet = Snippet::Between { start, end };
-
-        Self {
-            file,
-            snippet,
-            replacement: replacement.into(),
-            #[cfg(feature = "symbol_path")]
-            symbol_path: None,
-        }
+        Self::new(file, snippet, replacement)
     }
 }
+
+/// Returns whether a snippet failed to resolve because a target does not occur.
+fn is_not_found(err: &SnippetError) -> bool {
+    matches!(
+        err,
+        SnippetError::BoundaryError(BoundaryError::TargetError(TargetError::NotFound))
+    )
+}
+
+#[cfg(test)]
+#[path = "tests/patch_operations.rs"]
+mod patch_operations;
+
+#[cfg(test)]
+#[path = "tests/expected_text.rs"]
+mod expected_text;
+
+#[cfg(test)]
+#[path = "tests/idempotent.rs"]
+mod idempotent;
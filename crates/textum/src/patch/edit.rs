@@ -0,0 +1,84 @@
+//! Concrete edits produced by resolving a patch against a rope.
+
+use ropey::Rope;
+
+use super::PatchError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A resolved patch: the character range `[start, end)` to remove and the text to insert there.
+///
+/// Every [`Operation`](super::Operation) reduces to a single edit, which makes resolved patches
+/// directly comparable when checking for overlaps.
+pub struct Edit {
+    /// The starting character index of the range to replace.
+    pub start: usize,
+    /// The ending character index of the range to replace (exclusive).
+    pub end: usize,
+    /// The text inserted at `start` once the range is removed.
+    pub text: String,
+}
+
+impl Edit {
+    /// Create an edit replacing `[start, end)` with `text`.
+    #[must_use]
+    pub fn new(start: usize, end: usize, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+        }
+    }
+
+    /// Returns whether this edit only inserts text, removing nothing.
+    #[must_use]
+    pub fn is_insertion(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns whether applying this edit to `rope` would leave its text unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::Edit;
+    ///
+    /// let rope = Rope::from_str("hello world");
+    /// assert!(Edit::new(6, 11, "world").is_no_op(&rope));
+    /// assert!(!Edit::new(6, 11, "rust").is_no_op(&rope));
+    /// ```
+    #[must_use]
+    pub fn is_no_op(&self, rope: &Rope) -> bool {
+        self.start <= self.end
+            && self.end <= rope.len_chars()
+            && rope.slice(self.start..self.end) == self.text.as_str()
+    }
+
+    /// Apply this edit to a rope in-place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::RangeOutOfBounds`] if the range is inverted or extends beyond the
+    /// rope's character count. The rope is left unchanged in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::Edit;
+    ///
+    /// let mut rope = Rope::from_str("hello world");
+    /// Edit::new(6, 11, "rust").apply(&mut rope).unwrap();
+    /// assert_eq!(rope.to_string(), "hello rust");
+    /// ```
+    pub fn apply(&self, rope: &mut Rope) -> Result<(), PatchError> {
+        if self.start > self.end || self.end > rope.len_chars() {
+            return Err(PatchError::RangeOutOfBounds);
+        }
+        if self.start < self.end {
+            rope.remove(self.start..self.end);
+        }
+        rope.insert(self.start, &self.text);
+        Ok(())
+    }
+}
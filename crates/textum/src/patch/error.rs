@@ -1,7 +1,8 @@
 //! Error types for patch operations.
 
+use super::{Expected, Patch};
 use crate::snip::target::error::TargetError;
-use crate::snip::{BoundaryError, SnippetError};
+use crate::snip::{BoundaryError, MatchCount, SnippetError, Target};
 use std::fmt;
 
 /// Errors that can occur when applying patches.
@@ -10,12 +11,76 @@ pub enum PatchError {
     /// The patch range exceeds the file's character count.
     RangeOutOfBounds,
 
-    /// The target file could not be found or read.
-    FileNotFound,
+    /// The target file does not exist, or was deleted or moved earlier in the patch set.
+    FileNotFound {
+        /// The missing file.
+        file: String,
+    },
+
+    /// A patch in a patch set failed.
+    ///
+    /// Wraps the error with the identity of the patch that caused it.
+    InPatch {
+        /// Position of the patch in its set, counting from zero.
+        index: usize,
+        /// The patch's id, if it has one.
+        id: Option<String>,
+        /// File the patch targets.
+        file: String,
+        /// The error the patch caused.
+        source: Box<PatchError>,
+    },
+
+    /// A patch depends on an id that no patch in the set has.
+    UnknownDependency {
+        /// The missing id.
+        id: String,
+    },
+
+    /// Two patches in a set have the same id.
+    DuplicateId {
+        /// The repeated id.
+        id: String,
+    },
+
+    /// A patch depends on a patch that failed, when a set continues past failures.
+    DependencyFailed {
+        /// The id of the failed dependency.
+        id: String,
+    },
+
+    /// Patch dependencies form a cycle.
+    DependencyCycle {
+        /// Ids of the patches in the cycle, each depending on the next and the last on the
+        /// first.
+        ids: Vec<String>,
+    },
+
+    /// A file to be created, or the destination of a rename, already exists.
+    FileExists {
+        /// The existing file.
+        file: String,
+    },
+
+    /// A file changed on disk since textum last read or wrote it.
+    FileChanged {
+        /// The file that changed.
+        file: String,
+    },
 
     /// An I/O error occurred while reading or writing files.
     IoError(std::io::Error),
 
+    /// A file could not be written, moved or removed while writing a patch set's changes.
+    ///
+    /// Every file already changed was restored.
+    WriteFailed {
+        /// The file being changed.
+        file: String,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+
     /// An error occurred during snippet resolution.
     SnippetError(SnippetError),
 
@@ -25,12 +90,40 @@ pub enum PatchError {
     /// An error occurred during target resolution.
     TargetError(TargetError),
 
-    /// Resolved patch ranges overlap with non-empty replacements.
+    /// Resolved patch ranges overlap.
     OverlappingRanges {
-        /// First overlapping range.
+        /// File the patches target.
+        file: String,
+        /// Range of the patch declared first.
         range1: (usize, usize),
-        /// Second overlapping range.
+        /// Range of the patch declared second.
         range2: (usize, usize),
+        /// The two patches, in declaration order.
+        patches: Box<[PatchRef; 2]>,
+    },
+
+    /// A boundary target occurred a different number of times than its expected count.
+    MatchCountMismatch {
+        /// File in which the target was counted.
+        file: String,
+        /// The target whose occurrences were counted.
+        target: Target,
+        /// The count constraint declared on the boundary.
+        expected: MatchCount,
+        /// The number of occurrences actually found.
+        actual: usize,
+        /// Character ranges of every occurrence found.
+        positions: Vec<(usize, usize)>,
+    },
+
+    /// The resolved range did not contain the original text the patch expected.
+    ExpectedMismatch {
+        /// File the patch targets.
+        file: String,
+        /// The precondition declared on the patch.
+        expected: Expected,
+        /// The text actually found in the resolved range.
+        actual: String,
     },
 }
 
@@ -38,19 +131,123 @@ impl fmt::Display for PatchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::RangeOutOfBounds => write!(f, "Patch range exceeds file bounds"),
-            Self::FileNotFound => write!(f, "Target file not found"),
+            Self::FileNotFound { file } => write!(f, "Target file not found: {file}"),
+            Self::FileExists { file } => write!(f, "File already exists: {file}"),
+            Self::InPatch {
+                index,
+                id: Some(id),
+                file,
+                source,
+            } => write!(f, "Patch {index} ({id:?}) in {file}: {source}"),
+            Self::InPatch {
+                index,
+                id: None,
+                file,
+                source,
+            } => write!(f, "Patch {index} in {file}: {source}"),
+            Self::UnknownDependency { id } => write!(f, "Depends on unknown patch id {id:?}"),
+            Self::DuplicateId { id } => write!(f, "Duplicate patch id {id:?}"),
+            Self::DependencyFailed { id } => write!(f, "Dependency {id:?} was not applied"),
+            Self::DependencyCycle { ids } => write!(f, "Dependency cycle: {}", ids.join(" -> ")),
+            Self::FileChanged { file } => {
+                write!(f, "File changed since textum last read or wrote it: {file}")
+            }
             Self::IoError(e) => write!(f, "I/O error: {e}"),
+            Self::WriteFailed { file, source } => write!(f, "Failed to write {file}: {source}"),
             Self::SnippetError(e) => write!(f, "Snippet error: {e:?}"),
             Self::BoundaryError(e) => write!(f, "Boundary error: {e:?}"),
             Self::TargetError(e) => write!(f, "Target error: {e}"),
-            Self::OverlappingRanges { range1, range2 } => {
-                write!(f, "Overlapping ranges: {range1:?} and {range2:?}")
-            }
+            Self::OverlappingRanges {
+                file,
+                range1,
+                range2,
+                patches,
+            } => write!(
+                f,
+                "Overlapping ranges in {file}: {range1:?} from {} and {range2:?} from {}",
+                patches[0], patches[1],
+            ),
+            Self::MatchCountMismatch {
+                file,
+                target,
+                expected,
+                actual,
+                positions,
+            } => write!(
+                f,
+                "Expected {expected} match(es) of {target:?} in {file}, found {actual} at {positions:?}"
+            ),
+            Self::ExpectedMismatch {
+                file,
+                expected,
+                actual,
+            } => write!(f, "Expected {expected} in {file}, found {actual:?}"),
         }
     }
 }
 
-impl std::error::Error for PatchError {}
+/// Identifies a patch within its set.
+///
+/// Displayed as its index and id, e.g. `patch 2 ("rename")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchRef {
+    /// Position of the patch in its set, counting from zero.
+    pub index: usize,
+    /// The patch's id, if it has one.
+    pub id: Option<String>,
+}
+
+impl PatchRef {
+    /// Identify the patch at `index` in its set.
+    #[must_use]
+    pub fn new(index: usize, patch: &Patch) -> Self {
+        Self {
+            index,
+            id: patch.id.clone(),
+        }
+    }
+}
+
+impl fmt::Display for PatchRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.id {
+            Some(id) => write!(f, "patch {} ({id:?})", self.index),
+            None => write!(f, "patch {}", self.index),
+        }
+    }
+}
+
+impl PatchError {
+    /// Wraps this error with the identity of the patch at `index` in its set.
+    #[must_use]
+    pub fn in_patch(self, index: usize, patch: &Patch) -> Self {
+        Self::InPatch {
+            index,
+            id: patch.id.clone(),
+            file: patch.file.clone(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the error without any patch identity it was wrapped with.
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::InPatch { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InPatch { source, .. } => Some(source.as_ref()),
+            Self::IoError(e) | Self::WriteFailed { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for PatchError {
     fn from(e: std::io::Error) -> Self {
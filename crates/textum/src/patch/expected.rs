@@ -0,0 +1,83 @@
+//! Preconditions on the original text a patch expects to replace.
+
+use std::fmt;
+use std::fmt::Write as _;
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// The original text a patch was authored against.
+///
+/// When set on a patch, the text of the resolved range must match before the patch is applied,
+/// so that a file which changed since the patch was written is reported instead of silently
+/// edited.
+///
+/// # Examples
+///
+/// ```
+/// use textum::Expected;
+///
+/// assert!(Expected::Text("old".to_string()).matches("old"));
+/// assert!(Expected::hash_of("old").matches("old"));
+/// assert!(!Expected::hash_of("old").matches("new"));
+/// ```
+pub enum Expected {
+    /// The range must contain exactly this text.
+    Text(String),
+    /// The SHA-256 digest of the range's text must equal this lowercase hex string.
+    Hash(String),
+}
+
+impl Expected {
+    /// Create a hash precondition from the text it should match.
+    #[must_use]
+    pub fn hash_of(text: &str) -> Self {
+        Self::Hash(content_hash(text))
+    }
+
+    /// Returns whether `actual` satisfies this precondition.
+    ///
+    /// Hashes are compared case-insensitively.
+    #[must_use]
+    pub fn matches(&self, actual: &str) -> bool {
+        match self {
+            Self::Text(text) => text == actual,
+            Self::Hash(hash) => hash.eq_ignore_ascii_case(&content_hash(actual)),
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{text:?}"),
+            Self::Hash(hash) => write!(f, "text with SHA-256 {hash}"),
+        }
+    }
+}
+
+/// Returns the lowercase hex SHA-256 digest of `text`, as used by [`Expected::Hash`].
+///
+/// # Examples
+///
+/// ```
+/// use textum::content_hash;
+///
+/// assert_eq!(
+///     content_hash("abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+/// );
+/// ```
+#[must_use]
+pub fn content_hash(text: &str) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
@@ -0,0 +1,145 @@
+//! Re-indenting replacement text to match the site it is inserted at.
+//!
+//! Generated replacements are usually written at column zero. Re-indenting strips the
+//! replacement's common leading whitespace and prefixes its lines with the indentation of the
+//! line being patched, keeping each line's indentation relative to the others.
+
+use ropey::Rope;
+use std::ops::Range;
+
+/// Width in spaces of one level of tab indentation, used when a tab-indented replacement is
+/// inserted into space-indented text.
+const TAB_WIDTH: usize = 4;
+
+/// Returns the leading whitespace of the line containing character index `char_idx`.
+#[must_use]
+pub fn line_indent(rope: &Rope, char_idx: usize) -> String {
+    let line = rope.line(rope.char_to_line(char_idx));
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+/// Re-indents `text` for insertion in place of the character range `at` of the rope (empty for
+/// a pure insertion), relative to the indentation of the line containing character index
+/// `anchor`.
+///
+/// Every non-blank line after the first is prefixed with the anchor line's indentation. The first
+/// line is prefixed only with whatever part of that indentation does not already precede the
+/// insertion point, so replacing an indented line or inserting at a line start both work, while
+/// inserting after code on a line adds nothing. If `at` starts within a line's indentation, the
+/// text ends with a newline and the line goes on after `at` with more than whitespace, that
+/// indentation is repeated after the text so what followed keeps its column. Blank lines are
+/// emptied. When the anchor line is indented with tabs and the replacement with spaces (or vice
+/// versa), the replacement's relative indentation is converted to the anchor's style.
+///
+/// # Examples
+///
+/// ```
+/// use ropey::Rope;
+/// use textum::patch::indent::reindent;
+///
+/// let rope = Rope::from_str("def f():\n    pass\n");
+/// let body = "if x:\n    return 1\n";
+///
+/// // Replacing the whole `pass` line, which starts at character 9
+/// assert_eq!(reindent(body, &rope, 9, 9..17), "    if x:\n        return 1\n");
+/// ```
+#[must_use]
+pub fn reindent(text: &str, rope: &Rope, anchor: usize, at: Range<usize>) -> String {
+    let indent = line_indent(rope, anchor);
+    let line_start = rope.line_to_char(rope.char_to_line(at.start));
+    let before: String = rope.slice(line_start..at.start).chars().collect();
+    let first_prefix = indent.strip_prefix(before.as_str()).unwrap_or("");
+
+    let common = common_indent(text);
+    let unit = space_unit(text, common.len());
+    let use_tabs = indent.starts_with('\t');
+
+    let mut out = String::with_capacity(text.len() + indent.len() * 4);
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+        if content.trim().is_empty() {
+            out.push_str(ending);
+            continue;
+        }
+        let relative = &content[common.len().min(content.len())..];
+        let body = relative.trim_start_matches([' ', '\t']);
+        let leading = &relative[..relative.len() - body.len()];
+
+        out.push_str(if i == 0 { first_prefix } else { &indent });
+        push_converted(&mut out, leading, use_tabs, !indent.is_empty(), unit);
+        out.push_str(body);
+        out.push_str(ending);
+    }
+    let followed = rope
+        .chars_at(at.end)
+        .take_while(|c| *c != '\n' && *c != '\r')
+        .any(|c| c != ' ' && c != '\t');
+    if out.ends_with('\n') && followed && before.chars().all(|c| c == ' ' || c == '\t') {
+        out.push_str(&before);
+    }
+    out
+}
+
+/// Returns the longest whitespace prefix shared by all non-blank lines of `text`.
+fn common_indent(text: &str) -> &str {
+    let mut common: Option<&str> = None;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let ws = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        common = Some(match common {
+            None => ws,
+            Some(prev) => {
+                let shared = prev
+                    .bytes()
+                    .zip(ws.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &prev[..shared]
+            }
+        });
+    }
+    common.unwrap_or("")
+}
+
+/// Returns the smallest non-zero run of leading spaces beyond the common indentation, taken as
+/// the width of one indentation level in a space-indented replacement.
+fn space_unit(text: &str, common: usize) -> usize {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            l.get(common..)
+                .unwrap_or("")
+                .chars()
+                .take_while(|c| *c == ' ')
+                .count()
+        })
+        .filter(|n| *n > 0)
+        .min()
+        .unwrap_or(TAB_WIDTH)
+}
+
+/// Appends a line's relative indentation in the target style.
+fn push_converted(out: &mut String, leading: &str, use_tabs: bool, convert: bool, unit: usize) {
+    if !convert {
+        out.push_str(leading);
+    } else if use_tabs {
+        let spaces = leading.chars().filter(|c| *c == ' ').count();
+        let tabs = leading.chars().filter(|c| *c == '\t').count();
+        out.extend(std::iter::repeat_n('\t', tabs + spaces / unit));
+        out.extend(std::iter::repeat_n(' ', spaces % unit));
+    } else {
+        for c in leading.chars() {
+            if c == '\t' {
+                out.extend(std::iter::repeat_n(' ', TAB_WIDTH));
+            } else {
+                out.push(c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/reindent.rs"]
+mod reindent_tests;
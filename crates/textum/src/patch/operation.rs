@@ -0,0 +1,159 @@
+//! The kinds of edit a patch can make to its resolved range.
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+use ropey::Rope;
+
+use super::edit::Edit;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// What a patch does with the range its snippet resolves to.
+///
+/// The operation records the intent of a patch explicitly, so that inserting next to a match
+/// does not have to be encoded through boundary modes.
+///
+/// # Examples
+///
+/// ```
+/// use ropey::Rope;
+/// use textum::{BoundaryMode, Operation, Patch};
+///
+/// let mut rope = Rope::from_str("fn main() {}");
+/// let patch = Patch::from_literal_target("main.rs".to_string(), "fn", BoundaryMode::Include, "pub ")
+///     .with_operation(Operation::InsertBefore);
+///
+/// patch.apply(&mut rope).unwrap();
+/// assert_eq!(rope.to_string(), "pub fn main() {}");
+/// ```
+pub enum Operation {
+    /// Replace the resolved range with the replacement text.
+    #[default]
+    Replace,
+    /// Insert the replacement text immediately before the resolved range.
+    InsertBefore,
+    /// Insert the replacement text immediately after the resolved range.
+    InsertAfter,
+    /// Remove the resolved range. The replacement text is ignored.
+    Delete,
+    /// Surround the resolved range with a prefix and suffix. The replacement text is ignored.
+    Wrap {
+        /// Text inserted before the range.
+        prefix: String,
+        /// Text inserted after the range.
+        suffix: String,
+    },
+    /// Insert the replacement text at the start of the file. The snippet is not resolved.
+    Prepend,
+    /// Insert the replacement text at the end of the file. The snippet is not resolved.
+    Append,
+    /// Create the file with the replacement text as its contents. The snippet is not resolved.
+    ///
+    /// Fails if the file already exists, unless `overwrite` is set.
+    CreateFile {
+        /// Replace the contents of the file if it already exists.
+        #[cfg_attr(feature = "facet", facet(default))]
+        overwrite: bool,
+    },
+    /// Delete the file. The snippet is not resolved and the replacement text is ignored.
+    ///
+    /// An [`Expected`](crate::Expected) precondition on the patch is checked against the whole
+    /// file, so a file is only deleted if it still has the contents the patch was written for.
+    DeleteFile,
+    /// Move the file to a new path. The snippet is not resolved and the replacement text is
+    /// ignored.
+    ///
+    /// Fails if the destination already exists.
+    RenameFile {
+        /// Path the file is moved to.
+        to: String,
+    },
+}
+
+impl Operation {
+    /// Returns whether this operation ignores the patch's snippet.
+    #[must_use]
+    pub fn is_file_level(&self) -> bool {
+        matches!(self, Self::Prepend | Self::Append) || self.is_file_operation()
+    }
+
+    /// Returns whether this operation creates, deletes or moves a file rather than editing text.
+    ///
+    /// Applied to a rope on its own, creating a file replaces its contents, deleting it empties
+    /// it and renaming it leaves it unchanged.
+    #[must_use]
+    pub fn is_file_operation(&self) -> bool {
+        matches!(
+            self,
+            Self::CreateFile { .. } | Self::DeleteFile | Self::RenameFile { .. }
+        )
+    }
+
+    /// Returns whether the text this operation would produce is already present around the
+    /// range `[start, end)` of the rope.
+    ///
+    /// Insertions are considered applied when the inserted text already sits on the relevant side
+    /// of the range, replacements when the range lies within an occurrence of the replacement
+    /// text, removals when the range is empty, and file creation when the range holds exactly
+    /// the replacement text.
+    #[must_use]
+    pub fn is_applied(&self, rope: &Rope, start: usize, end: usize, replacement: &str) -> bool {
+        match self {
+            Self::Replace if replacement.is_empty() => start == end,
+            Self::Replace => {
+                // The replacement may embed the original text, which then still matches
+                let matched = rope.slice(start..end).to_string();
+                replacement.match_indices(&matched).any(|(offset, _)| {
+                    let offset = replacement[..offset].chars().count();
+                    offset <= start && has_text_at(rope, start - offset, replacement)
+                })
+            }
+            Self::Delete => start == end,
+            Self::CreateFile { .. } => {
+                end - start == replacement.chars().count() && has_text_at(rope, start, replacement)
+            }
+            // Whether a file was deleted or moved depends on which files exist, not on its text
+            Self::DeleteFile | Self::RenameFile { .. } => false,
+            Self::InsertBefore | Self::Append => has_text_before(rope, start, replacement),
+            Self::InsertAfter | Self::Prepend => has_text_at(rope, end, replacement),
+            Self::Wrap { prefix, suffix } => {
+                has_text_before(rope, start, prefix) && has_text_at(rope, end, suffix)
+            }
+        }
+    }
+
+    /// Builds the edit this operation makes to the range `[start, end)` of the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range lies outside the rope.
+    #[must_use]
+    pub fn edit(&self, rope: &Rope, start: usize, end: usize, replacement: &str) -> Edit {
+        match self {
+            Self::Replace | Self::Prepend | Self::Append | Self::CreateFile { .. } => {
+                Edit::new(start, end, replacement)
+            }
+            Self::InsertBefore => Edit::new(start, start, replacement),
+            Self::InsertAfter => Edit::new(end, end, replacement),
+            Self::Delete | Self::DeleteFile => Edit::new(start, end, ""),
+            Self::RenameFile { .. } => Edit::new(start, start, ""),
+            Self::Wrap { prefix, suffix } => {
+                let inner = rope.slice(start..end);
+                Edit::new(start, end, format!("{prefix}{inner}{suffix}"))
+            }
+        }
+    }
+}
+
+/// Returns whether `text` occurs in the rope starting at character index `idx`.
+fn has_text_at(rope: &Rope, idx: usize, text: &str) -> bool {
+    let len = text.chars().count();
+    idx + len <= rope.len_chars() && rope.slice(idx..idx + len) == text
+}
+
+/// Returns whether `text` occurs in the rope ending at character index `idx`.
+fn has_text_before(rope: &Rope, idx: usize, text: &str) -> bool {
+    let len = text.chars().count();
+    len <= idx && rope.slice(idx - len..idx) == text
+}
@@ -0,0 +1,25 @@
+//! Outcomes of applying a single patch.
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// What happened when a patch was applied.
+pub enum PatchStatus {
+    /// The patch changed the text.
+    Applied,
+    /// The patch is idempotent and its change was already present, so nothing was done.
+    ///
+    /// In a [`PatchSet`](crate::PatchSet), also reported for a patch whose edit was dropped by
+    /// the set's [`OverlapPolicy`](crate::OverlapPolicy), or whose file was left unchanged
+    /// because another patch to it failed.
+    Skipped,
+    /// The patch was applied, but replaced its range with identical text.
+    NoOp,
+    /// The patch could not be applied, and its [`PatchSet`](crate::PatchSet) carried on
+    /// without it under [`Atomicity::File`](crate::Atomicity::File) or
+    /// [`Atomicity::Patch`](crate::Atomicity::Patch).
+    Failed,
+}
@@ -7,9 +7,12 @@
 //! could be a major form of error).
 
 pub mod snippet;
+pub mod span;
 pub mod target;
 
 pub use snippet::{
-    Boundary, BoundaryError, BoundaryMode, Extent, Snippet, SnippetError, SnippetResolution,
+    Boundary, BoundaryError, BoundaryMode, Extent, MatchCount, Snippet, SnippetError,
+    SnippetResolution,
 };
+pub use span::{Location, Span};
 pub use target::Target;
@@ -13,11 +13,11 @@ pub mod replacement;
 /// Snippet resolution to rope indices.
 pub mod resolution;
 
-pub use boundary::{Boundary, BoundaryError, BoundaryMode, Extent};
+pub use boundary::{Boundary, BoundaryError, BoundaryMode, Extent, MatchCount};
 pub use error::*;
 pub use resolution::*;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "facet", derive(Facet))]
 #[repr(u8)]
 /// Specifies a text range through boundary markers or positions.
@@ -41,5 +41,22 @@ pub enum Snippet {
         end: Boundary,
     },
     /// Selects the entire file.
+    #[default]
     All,
 }
+
+impl Snippet {
+    /// Returns the boundaries defining this snippet, in start-to-end order.
+    ///
+    /// `All` has no boundaries; `Between` has two; every other variant has one.
+    #[must_use]
+    pub fn boundaries(&self) -> Vec<&Boundary> {
+        match self {
+            Snippet::At(boundary) | Snippet::From(boundary) | Snippet::To(boundary) => {
+                vec![boundary]
+            }
+            Snippet::Between { start, end } => vec![start, end],
+            Snippet::All => vec![],
+        }
+    }
+}
@@ -5,6 +5,8 @@ use facet::Facet;
 
 use crate::snip::Target;
 
+/// Occurrence count constraints for boundary targets.
+pub mod count;
 /// `BoundaryError` enum type raised by boundary resolution.
 pub mod error;
 /// Extent configuration for boundary expansion.
@@ -14,6 +16,7 @@ pub mod mode;
 /// Boundary resolution struct and `Boundary::resolve` implementation.
 pub mod resolution;
 
+pub use count::*;
 pub use error::*;
 pub use extent::*;
 pub use mode::*;
@@ -27,12 +30,38 @@ pub struct Boundary {
     pub target: Target,
     /// Whether to include, exclude, or extend beyond this boundary.
     pub mode: BoundaryMode,
+    /// Optional assertion on how many times the target occurs in the file.
+    ///
+    /// Checked by [`crate::PatchSet`] before any patch is applied.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub expected_count: Option<MatchCount>,
 }
 
 impl Boundary {
     #[must_use]
     /// Constructs a boundary from a target and mode.
     pub fn new(target: Target, mode: BoundaryMode) -> Self {
-        Self { target, mode }
+        Self {
+            target,
+            mode,
+            expected_count: None,
+        }
+    }
+
+    #[must_use]
+    /// Requires the target to occur a given number of times for this boundary to be used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{Boundary, BoundaryMode, MatchCount, Target};
+    ///
+    /// let boundary = Boundary::new(Target::Literal("id".to_string()), BoundaryMode::Include)
+    ///     .with_expected_count(MatchCount::Exactly(3));
+    /// assert_eq!(boundary.expected_count, Some(MatchCount::Exactly(3)));
+    /// ```
+    pub fn with_expected_count(mut self, count: MatchCount) -> Self {
+        self.expected_count = Some(count);
+        self
     }
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::hash::Hash;
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+/// Constrains how many times a boundary's target may occur in the file.
+///
+/// Used as a safety assertion before a patch is applied: a literal that is expected to be unique
+/// can be declared `Exactly(1)` so that an ambiguous match is reported rather than silently
+/// resolving to the first occurrence.
+pub enum MatchCount {
+    /// The target must occur exactly this many times.
+    Exactly(usize),
+    /// The target must occur at least this many times.
+    AtLeast(usize),
+    /// The target must occur at most this many times.
+    AtMost(usize),
+    /// The target must occur between `min` and `max` times (both inclusive).
+    Range {
+        /// Minimum number of occurrences.
+        min: usize,
+        /// Maximum number of occurrences.
+        max: usize,
+    },
+}
+
+impl MatchCount {
+    /// Returns whether `count` occurrences satisfy this constraint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::MatchCount;
+    ///
+    /// assert!(MatchCount::Exactly(3).admits(3));
+    /// assert!(!MatchCount::AtLeast(1).admits(0));
+    /// assert!(MatchCount::Range { min: 1, max: 2 }.admits(2));
+    /// ```
+    #[must_use]
+    pub fn admits(&self, count: usize) -> bool {
+        match self {
+            Self::Exactly(n) => count == *n,
+            Self::AtLeast(n) => count >= *n,
+            Self::AtMost(n) => count <= *n,
+            Self::Range { min, max } => (*min..=*max).contains(&count),
+        }
+    }
+}
+
+impl fmt::Display for MatchCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exactly(n) => write!(f, "exactly {n}"),
+            Self::AtLeast(n) => write!(f, "at least {n}"),
+            Self::AtMost(n) => write!(f, "at most {n}"),
+            Self::Range { min, max } => write!(f, "between {min} and {max}"),
+        }
+    }
+}
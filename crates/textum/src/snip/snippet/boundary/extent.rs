@@ -240,7 +240,7 @@ pub fn calculate_matching_extent(
 
     // Check for invalid target types FIRST
     match target {
-        Target::Literal(needle) if needle.is_empty() => {
+        Target::Literal(needle) | Target::LiteralWith { needle, .. } if needle.is_empty() => {
             // Ambiguous: empty needle would match everywhere; treat as invalid for extent.
             return Err(BoundaryError::InvalidExtent);
         }
@@ -248,7 +248,7 @@ pub fn calculate_matching_extent(
             // Other Target kinds not meaningful for "Matching" (treat as invalid)
             return Err(BoundaryError::InvalidExtent);
         }
-        Target::Literal(_) => {} // Valid case: Literal with content
+        Target::Literal(_) | Target::LiteralWith { .. } => {} // Valid case: Literal with content
         #[cfg(feature = "regex")]
         Target::Pattern(_) => {} // Valid case: Pattern
     }
@@ -322,6 +322,14 @@ pub fn calculate_matching_extent(
                 remaining = remaining.saturating_sub(1);
             }
 
+            Target::LiteralWith { needle, options } => {
+                let (_, match_end) =
+                    crate::snip::target::literal::find_literal(rope, needle, options, cursor)
+                        .ok_or(BoundaryError::ExtentOutOfBounds)?;
+                cursor = match_end;
+                remaining = remaining.saturating_sub(1);
+            }
+
             #[cfg(feature = "regex")]
             Target::Pattern(pattern) => {
                 use regex_cursor::{Input as RegexInput, RopeyCursor};
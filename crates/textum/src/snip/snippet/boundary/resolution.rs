@@ -2,6 +2,7 @@ use super::{
     calculate_bytes_extent, calculate_chars_extent, calculate_lines_extent,
     calculate_matching_extent, Boundary, BoundaryError, BoundaryMode, Extent,
 };
+use crate::snip::span::Span;
 use ropey::Rope;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -16,6 +17,20 @@ pub struct BoundaryResolution {
     pub end: usize,
 }
 
+impl BoundaryResolution {
+    /// Describes this resolution in every coordinate system, together with the selected text.
+    ///
+    /// The rope must be the one the boundary was resolved against.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution lies outside the rope.
+    #[must_use]
+    pub fn span(&self, rope: &Rope) -> Span {
+        Span::new(rope, self.start, self.end)
+    }
+}
+
 impl Boundary {
     /// Resolves this boundary into a pair of absolute character indices.
     ///
@@ -10,6 +10,9 @@ impl Snippet {
     /// This method resolves the snippet's boundaries to determine the target range, validates
     /// the replacement text, and returns a new [`Rope`] with the replacement applied.
     ///
+    /// The replacement is a template: each `{{match}}` in it is replaced by the selected text
+    /// (see [`expand_match_template`] for the escaping rules).
+    ///
     /// # Behavior by Snippet Type
     ///
     /// - **Zero-width range** (start == end): Performs insertion at the position
@@ -80,18 +83,146 @@ impl Snippet {
     /// let result = snippet.replace(&rope, "new content").unwrap();
     /// assert_eq!(result.to_string(), "<!-- comment -->new content<!-- /comment -->");
     /// ```
+    ///
+    /// Refer to the selected text:
+    ///
+    /// ```rust
+    /// use textum::{Snippet, Target, Boundary, BoundaryMode};
+    /// use ropey::Rope;
+    ///
+    /// let rope = Rope::from_str("let x = value;");
+    /// let boundary = Boundary::new(Target::Literal("value".to_string()), BoundaryMode::Include);
+    /// let snippet = Snippet::At(boundary);
+    ///
+    /// let result = snippet.replace(&rope, "Some({{match}})").unwrap();
+    /// assert_eq!(result.to_string(), "let x = Some(value);");
+    /// ```
     pub fn replace(&self, rope: &Rope, replacement: &str) -> Result<Rope, SnippetError> {
         validate_replacement_utf8(replacement)?;
         let resolution = self.resolve(rope)?;
+        let matched = rope.slice(resolution.start..resolution.end).to_string();
         Ok(apply_replacement(
             rope,
             resolution.start,
             resolution.end,
-            replacement,
+            &expand_match_template(replacement, &matched),
         ))
     }
 }
 
+/// The placeholder that a replacement template uses to refer to the selected text.
+pub const MATCH_PLACEHOLDER: &str = "{{match}}";
+
+/// Expands each `{{match}}` placeholder in a replacement template to the matched text.
+///
+/// Backslashes are only special directly before a placeholder: a run of backslashes there is
+/// halved, and if the run had odd length the placeholder is kept literally instead of expanded.
+/// So `\{{match}}` yields the text `{{match}}`, and `\\{{match}}` yields a backslash followed
+/// by the match. Backslashes anywhere else are copied unchanged. In JSON, where a backslash is
+/// itself written `\\`, an escaped placeholder is written `"\\{{match}}"`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use textum::snip::snippet::replacement::expand_match_template;
+/// assert_eq!(expand_match_template("Some({{match}})", "x"), "Some(x)");
+/// assert_eq!(expand_match_template("{{match}}{{match}}", "ab"), "abab");
+/// assert_eq!(expand_match_template(r"\{{match}}", "x"), "{{match}}");
+/// assert_eq!(expand_match_template(r"\\{{match}}", "x"), r"\x");
+/// assert_eq!(expand_match_template(r"C:\dir", "x"), r"C:\dir");
+/// ```
+#[must_use]
+pub fn expand_match_template(template: &str, matched: &str) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    for_each_template_part(template, |part| match part {
+        TemplatePart::Text(text) => expanded.push_str(text),
+        TemplatePart::Backslashes(n) => expanded.extend(std::iter::repeat_n('\\', n)),
+        TemplatePart::Match => expanded.push_str(matched),
+    });
+    expanded
+}
+
+/// Escapes `text` so that, as a replacement template, it expands to itself whatever the match.
+///
+/// Each `{{match}}` in `text`, and the backslashes directly before it, are escaped following
+/// the rules of [`expand_match_template`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use textum::snip::snippet::replacement::{escape_match_template, expand_match_template};
+/// assert_eq!(escape_match_template("Some({{match}})"), r"Some(\{{match}})");
+/// for text in ["plain", r"C:\dir", "{{match}}", r"\{{match}}", r"a\\{{match}}b"] {
+///     assert_eq!(expand_match_template(&escape_match_template(text), "x"), text);
+/// }
+/// ```
+#[must_use]
+pub fn escape_match_template(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(MATCH_PLACEHOLDER) {
+        let before = &rest[..pos];
+        let literal = before.trim_end_matches('\\');
+        let backslashes = before.len() - literal.len();
+
+        escaped.push_str(literal);
+        escaped.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+        escaped.push_str(MATCH_PLACEHOLDER);
+        rest = &rest[pos + MATCH_PLACEHOLDER.len()..];
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+/// Returns whether a replacement template contains an unescaped `{{match}}` placeholder.
+///
+/// # Examples
+///
+/// ```rust
+/// # use textum::snip::snippet::replacement::has_match_placeholder;
+/// assert!(has_match_placeholder("Some({{match}})"));
+/// assert!(!has_match_placeholder(r"\{{match}}"));
+/// assert!(!has_match_placeholder("plain"));
+/// ```
+#[must_use]
+pub fn has_match_placeholder(template: &str) -> bool {
+    let mut found = false;
+    for_each_template_part(template, |part| {
+        found |= matches!(part, TemplatePart::Match);
+    });
+    found
+}
+
+/// A piece of a parsed replacement template.
+enum TemplatePart<'a> {
+    /// Literal text, copied as-is.
+    Text(&'a str),
+    /// A run of literal backslashes left after unescaping.
+    Backslashes(usize),
+    /// An unescaped placeholder for the matched text.
+    Match,
+}
+
+/// Splits a template into literal text and placeholders, applying the escaping rules.
+fn for_each_template_part<'a>(template: &'a str, mut visit: impl FnMut(TemplatePart<'a>)) {
+    let mut rest = template;
+    while let Some(pos) = rest.find(MATCH_PLACEHOLDER) {
+        let before = &rest[..pos];
+        let literal = before.trim_end_matches('\\');
+        let escapes = before.len() - literal.len();
+
+        visit(TemplatePart::Text(literal));
+        visit(TemplatePart::Backslashes(escapes / 2));
+        if escapes % 2 == 1 {
+            visit(TemplatePart::Text(MATCH_PLACEHOLDER));
+        } else {
+            visit(TemplatePart::Match);
+        }
+        rest = &rest[pos + MATCH_PLACEHOLDER.len()..];
+    }
+    visit(TemplatePart::Text(rest));
+}
+
 /// Validates that a replacement string meets UTF-8 requirements.
 ///
 /// While Rust's `&str` type guarantees valid UTF-8, this function performs additional
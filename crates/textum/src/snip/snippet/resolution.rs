@@ -1,4 +1,5 @@
 use super::{Extent, Snippet, SnippetError};
+use crate::snip::span::Span;
 use ropey::Rope;
 
 use super::boundary::{
@@ -15,6 +16,35 @@ pub struct SnippetResolution {
     pub end: usize,
 }
 
+impl SnippetResolution {
+    /// Describes this resolution in every coordinate system, together with the selected text.
+    ///
+    /// The rope must be the one the snippet was resolved against.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution lies outside the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::{Boundary, BoundaryMode, Snippet, Target};
+    ///
+    /// let rope = Rope::from_str("one\ntwo\nthree\n");
+    /// let snippet = Snippet::At(Boundary::new(Target::Line(2), BoundaryMode::Include));
+    /// let span = snippet.resolve(&rope).unwrap().span(&rope);
+    ///
+    /// assert_eq!(span.text, "three\n");
+    /// assert_eq!(span.start.line_one_indexed(), 3);
+    /// assert_eq!(span.start.byte_idx, 8);
+    /// ```
+    #[must_use]
+    pub fn span(&self, rope: &Rope) -> Span {
+        Span::new(rope, self.start, self.end)
+    }
+}
+
 /// Validates that a range is valid and within rope bounds.
 fn validate_range(start: usize, end: usize, rope: &Rope) -> Result<(), SnippetError> {
     let rope_len = rope.len_chars();
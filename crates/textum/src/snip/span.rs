@@ -0,0 +1,129 @@
+//! Resolved ranges expressed in every coordinate system.
+//!
+//! Resolution works in character indices, but tools reporting what a patch selected usually need
+//! byte offsets (for UTF-8 APIs), line/column pairs (for humans and editors) or UTF-16 code unit
+//! offsets (for LSP and JavaScript). A [`Span`] carries all of these, plus the selected text.
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+use ropey::Rope;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+/// A single position in a rope, in character, byte, line/column and UTF-16 coordinates.
+///
+/// All fields are zero-indexed. Use [`Location::line_one_indexed`] and
+/// [`Location::col_one_indexed`] for the one-indexed convention used by [`crate::Target::Position`]
+/// and most editors.
+pub struct Location {
+    /// Character (Unicode scalar value) index.
+    pub char_idx: usize,
+    /// UTF-8 byte offset.
+    pub byte_idx: usize,
+    /// Zero-indexed line number.
+    pub line: usize,
+    /// Zero-indexed column, counted in characters from the start of the line.
+    pub col: usize,
+    /// UTF-16 code unit offset.
+    pub utf16_idx: usize,
+}
+
+impl Location {
+    /// Describes the position at character index `char_idx` of the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is greater than the rope's character count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::snip::span::Location;
+    ///
+    /// let rope = Rope::from_str("héllo\nwörld 🎉");
+    /// let loc = Location::new(&rope, 12);
+    /// assert_eq!((loc.line, loc.col), (1, 6));
+    /// assert_eq!(loc.byte_idx, 14);
+    /// assert_eq!(loc.utf16_idx, 12);
+    /// ```
+    #[must_use]
+    pub fn new(rope: &Rope, char_idx: usize) -> Self {
+        let line = rope.char_to_line(char_idx);
+        Self {
+            char_idx,
+            byte_idx: rope.char_to_byte(char_idx),
+            line,
+            col: char_idx - rope.line_to_char(line),
+            utf16_idx: rope.char_to_utf16_cu(char_idx),
+        }
+    }
+
+    /// Returns the one-indexed line number.
+    #[must_use]
+    pub fn line_one_indexed(&self) -> usize {
+        self.line + 1
+    }
+
+    /// Returns the one-indexed column number.
+    #[must_use]
+    pub fn col_one_indexed(&self) -> usize {
+        self.col + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+/// A resolved range with its start and end in every coordinate system, and the text it covers.
+pub struct Span {
+    /// Start of the range (inclusive).
+    pub start: Location,
+    /// End of the range (exclusive).
+    pub end: Location,
+    /// The text between `start` and `end`.
+    pub text: String,
+}
+
+impl Span {
+    /// Describes the character range `[start, end)` of the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or `end` is greater than the rope's character count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    /// use textum::snip::span::Span;
+    ///
+    /// let rope = Rope::from_str("fn main() {}\n");
+    /// let span = Span::new(&rope, 3, 7);
+    /// assert_eq!(span.text, "main");
+    /// assert_eq!(span.start.col_one_indexed(), 4);
+    /// ```
+    #[must_use]
+    pub fn new(rope: &Rope, start: usize, end: usize) -> Self {
+        Self {
+            start: Location::new(rope, start),
+            end: Location::new(rope, end),
+            text: rope.slice(start..end).to_string(),
+        }
+    }
+
+    /// Returns the length of the range in characters.
+    #[must_use]
+    pub fn len_chars(&self) -> usize {
+        self.end.char_idx - self.start.char_idx
+    }
+
+    /// Returns whether the range is zero-width.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start.char_idx == self.end.char_idx
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/span_coordinates.rs"]
+mod span_coordinates;
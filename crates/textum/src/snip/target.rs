@@ -5,8 +5,13 @@ use std::hash::{Hash, Hasher};
 use facet::Facet;
 
 pub mod error;
+pub mod literal;
 pub mod matching;
 
+pub use literal::LiteralOptions;
+#[cfg(feature = "normalization")]
+pub use literal::Normalization;
+
 #[cfg(feature = "regex")]
 use error::TargetError;
 
@@ -17,6 +22,14 @@ use error::TargetError;
 pub enum Target {
     /// An exact string to match.
     Literal(String),
+    /// A string to match with additional matching options, such as word boundaries.
+    LiteralWith {
+        /// The text to search for.
+        needle: String,
+        /// How the needle is compared against the text.
+        #[cfg_attr(feature = "facet", facet(default))]
+        options: LiteralOptions,
+    },
     #[cfg(feature = "regex")]
     /// Matches a regular expression pattern.
     Pattern(String),
@@ -34,6 +47,27 @@ pub enum Target {
 }
 
 impl Target {
+    /// Creates a literal target matched with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textum::{LiteralOptions, Target};
+    /// use ropey::Rope;
+    ///
+    /// let rope = Rope::from_str("valid id");
+    /// let options = LiteralOptions { word_boundary: true, ..Default::default() };
+    /// let target = Target::literal_with("id", options);
+    /// assert_eq!(target.resolve(&rope).unwrap(), 6);
+    /// ```
+    #[must_use]
+    pub fn literal_with(needle: impl Into<String>, options: LiteralOptions) -> Self {
+        Target::LiteralWith {
+            needle: needle.into(),
+            options,
+        }
+    }
+
     /// Creates a new Pattern target from a regex pattern string.
     ///
     /// # Errors
@@ -55,6 +89,16 @@ impl PartialEq for Target {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Target::Literal(a), Target::Literal(b)) => a == b,
+            (
+                Target::LiteralWith {
+                    needle: n1,
+                    options: o1,
+                },
+                Target::LiteralWith {
+                    needle: n2,
+                    options: o2,
+                },
+            ) => n1 == n2 && o1 == o2,
             #[cfg(feature = "regex")]
             (Target::Pattern(a), Target::Pattern(b)) => a == b,
             (Target::Line(a), Target::Line(b)) => a == b,
@@ -74,6 +118,11 @@ impl Hash for Target {
                 0u8.hash(state);
                 s.hash(state);
             }
+            Target::LiteralWith { needle, options } => {
+                5u8.hash(state);
+                needle.hash(state);
+                options.hash(state);
+            }
             #[cfg(feature = "regex")]
             Target::Pattern(s) => {
                 1u8.hash(state);
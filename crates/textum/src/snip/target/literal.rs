@@ -0,0 +1,358 @@
+//! Literal matching with configurable comparison options.
+
+use std::hash::Hash;
+
+#[cfg(feature = "facet")]
+use facet::Facet;
+use ropey::Rope;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+/// Options controlling how a [`crate::Target::LiteralWith`] needle is matched.
+///
+/// All options default to off, in which case matching is identical to [`crate::Target::Literal`].
+pub struct LiteralOptions {
+    /// Only accept matches that do not cut through a word.
+    ///
+    /// A match is rejected if its first character and the character before it are both word
+    /// characters, or if its last character and the character after it are both word
+    /// characters. Word characters are Unicode alphanumerics, combining marks and `_`, so
+    /// `id` matches in `(id)` and `id + 1` but not in `valid` or `idx`.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub word_boundary: bool,
+
+    /// Compare text case-insensitively using Unicode simple case folding.
+    ///
+    /// Folding maps each character to a single character, so matched ranges always cover the
+    /// same number of characters as the needle. Multi-character foldings (such as `ß` to `ss`)
+    /// are not applied.
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub case_insensitive: bool,
+
+    /// Compare text after Unicode normalization, so that differently encoded but equivalent
+    /// text (such as precomposed `é` and `e` followed by a combining acute accent) matches.
+    ///
+    /// Matches are reported as char ranges of the original, unnormalized text, and never split
+    /// a character from the combining marks that follow it.
+    #[cfg(feature = "normalization")]
+    #[cfg_attr(feature = "facet", facet(default))]
+    pub normalization: Option<Normalization>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[repr(u8)]
+#[cfg(feature = "normalization")]
+/// The Unicode normalization form used to compare literal text.
+pub enum Normalization {
+    /// Canonical composition: canonically equivalent text matches (NFC and NFD forms agree).
+    Nfc,
+    /// Compatibility composition: additionally matches compatibility equivalents, such as the
+    /// `ﬁ` ligature with `fi` or full-width digits with ASCII digits.
+    Nfkc,
+}
+
+impl LiteralOptions {
+    /// Returns whether any option transforms the text before comparison.
+    fn transforms_text(&self) -> bool {
+        #[cfg(feature = "normalization")]
+        if self.normalization.is_some() {
+            return true;
+        }
+        self.case_insensitive
+    }
+}
+
+/// Returns whether `c` counts as part of a word for boundary checks.
+///
+/// Combining diacritical marks are included so that decomposed accented letters are not split.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+        || c == '_'
+        || matches!(
+            c,
+            '\u{0300}'..='\u{036F}'
+                | '\u{1AB0}'..='\u{1AFF}'
+                | '\u{1DC0}'..='\u{1DFF}'
+                | '\u{20D0}'..='\u{20FF}'
+                | '\u{FE20}'..='\u{FE2F}'
+        )
+}
+
+/// Returns whether the char range `[start, end)` sits on word boundaries at both edges.
+fn on_word_boundaries(rope: &Rope, start: usize, end: usize) -> bool {
+    let joins = |left: usize, right: usize| {
+        left < rope.len_chars()
+            && right < rope.len_chars()
+            && is_word_char(rope.char(left))
+            && is_word_char(rope.char(right))
+    };
+    let joins_before = start > 0 && start < end && joins(start - 1, start);
+    let joins_after = end > start && joins(end - 1, end);
+    !joins_before && !joins_after
+}
+
+/// Finds the first match of `needle` starting at or after char index `from`.
+///
+/// Returns the `(start, end)` character range of the match, or `None` if there is none. An empty
+/// needle matches at `from`.
+#[must_use]
+pub fn find_literal(
+    rope: &Rope,
+    needle: &str,
+    options: &LiteralOptions,
+    from: usize,
+) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return (from <= rope.len_chars()).then_some((from, from));
+    }
+    find_literal_matches(rope, needle, options, from).next()
+}
+
+/// Finds every non-overlapping match of `needle` in the rope, in order of appearance.
+#[must_use]
+pub fn find_all_literal(
+    rope: &Rope,
+    needle: &str,
+    options: &LiteralOptions,
+) -> Vec<(usize, usize)> {
+    if needle.is_empty() {
+        return vec![(0, 0)];
+    }
+    find_literal_matches(rope, needle, options, 0).collect()
+}
+
+/// Iterates over matches of a non-empty `needle` from char index `from` onwards.
+fn find_literal_matches<'a>(
+    rope: &'a Rope,
+    needle: &'a str,
+    options: &'a LiteralOptions,
+    from: usize,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let from = from.min(rope.len_chars());
+    let candidates = if options.transforms_text() {
+        FoldedText::new(rope, from, options).candidates(&fold_str(needle, options))
+    } else {
+        exact_candidates(rope, needle, from)
+    };
+
+    let mut last_end = from;
+    candidates.into_iter().filter(move |&(start, end)| {
+        if start < last_end || (options.word_boundary && !on_word_boundaries(rope, start, end)) {
+            return false;
+        }
+        last_end = end;
+        true
+    })
+}
+
+/// Finds every (possibly overlapping) exact occurrence of `needle` from char index `from`.
+///
+/// Overlapping candidates are kept so that a match rejected by the boundary check does not hide
+/// an overlapping one that would be accepted.
+fn exact_candidates(rope: &Rope, needle: &str, from: usize) -> Vec<(usize, usize)> {
+    let haystack = rope.slice(from..).to_string();
+    let needle_len = needle.chars().count();
+    let base_byte = rope.char_to_byte(from);
+
+    let mut candidates = Vec::new();
+    let mut search_from = 0;
+    while let Some(rel) = haystack[search_from..].find(needle) {
+        let byte_idx = search_from + rel;
+        let start = rope.byte_to_char(base_byte + byte_idx);
+        candidates.push((start, start + needle_len));
+        search_from = byte_idx + next_char_len(&haystack[byte_idx..]);
+    }
+    candidates
+}
+
+/// Returns the UTF-8 length of the first char of `s` (1 for an empty string).
+fn next_char_len(s: &str) -> usize {
+    s.chars().next().map_or(1, char::len_utf8)
+}
+
+/// Maps a character to its Unicode simple case folding.
+///
+/// Simple case folding is a one-to-one mapping, so the folded text has the same number of
+/// characters as the original. For most characters it agrees with the single-character
+/// lowercase mapping; the exceptions below are characters that are already lowercase but fold to
+/// another letter (such as final sigma). Characters whose lowercase form is more than one
+/// character (such as `İ`) have no simple folding and are left unchanged.
+fn fold_case(c: char) -> char {
+    match c {
+        'µ' => 'μ',
+        'ſ' => 's',
+        '\u{0345}' | '\u{1FBE}' => 'ι',
+        'ς' => 'σ',
+        'ϐ' => 'β',
+        'ϑ' => 'θ',
+        'ϕ' => 'φ',
+        'ϖ' => 'π',
+        'ϰ' => 'κ',
+        'ϱ' => 'ρ',
+        'ϵ' => 'ε',
+        '\u{1E9B}' => '\u{1E61}',
+        '\u{FB05}' => '\u{FB06}',
+        _ => {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) => l,
+                _ => c,
+            }
+        }
+    }
+}
+
+/// Applies the comparison transforms selected in `options` to a string.
+fn fold_str(s: &str, options: &LiteralOptions) -> String {
+    #[cfg(feature = "normalization")]
+    let normalized = options.normalization.map(|form| form.apply(s.chars()));
+    #[cfg(feature = "normalization")]
+    let s = normalized.as_deref().unwrap_or(s);
+
+    if options.case_insensitive {
+        s.chars().map(fold_case).collect()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl Normalization {
+    /// Normalizes a sequence of chars into this form.
+    fn apply(self, chars: impl Iterator<Item = char>) -> String {
+        use unicode_normalization::UnicodeNormalization;
+
+        match self {
+            Normalization::Nfc => chars.nfc().collect(),
+            Normalization::Nfkc => chars.nfkc().collect(),
+        }
+    }
+}
+
+/// Splits the text from char index `from` into normalization units.
+///
+/// A unit is a starter (a char with canonical combining class zero) followed by any
+/// non-starters, extended with following starters that compose with it (such as Hangul jamo).
+/// Normalizing units independently gives the same result as normalizing the whole text, while
+/// keeping track of which original chars produced each normalized char.
+#[cfg(feature = "normalization")]
+fn normalization_units(rope: &Rope, from: usize) -> Vec<(usize, usize)> {
+    use unicode_normalization::char::{canonical_combining_class, compose};
+
+    let mut units: Vec<(usize, usize)> = Vec::new();
+    // The starter of the current unit, composed with everything that has combined into it
+    let mut starter: Option<char> = None;
+    for (offset, c) in rope.chars_at(from).enumerate() {
+        let idx = from + offset;
+        let composed = starter.and_then(|s| compose(s, c));
+        let continues_unit =
+            starter.is_some() && (composed.is_some() || canonical_combining_class(c) != 0);
+        match units.last_mut() {
+            Some(unit) if continues_unit => unit.1 = idx + 1,
+            _ => units.push((idx, idx + 1)),
+        }
+        if let Some(composed) = composed {
+            starter = Some(composed);
+        } else if !continues_unit {
+            starter = Some(c);
+        }
+    }
+    units
+}
+
+/// A transformed copy of the rope used for option-aware matching.
+///
+/// Each transformed char remembers the char range of the original text it came from, so matches
+/// found in the transformed text can be reported as ranges of the original rope.
+struct FoldedText {
+    /// The transformed text.
+    text: String,
+    /// Byte offset in `text` of each transformed char.
+    bytes: Vec<usize>,
+    /// Original `(start, end)` char range each transformed char came from.
+    origins: Vec<(usize, usize)>,
+    /// Whether each transformed char is the first produced by its original range.
+    unit_starts: Vec<bool>,
+    /// Whether each transformed char is the last produced by its original range.
+    unit_ends: Vec<bool>,
+}
+
+impl FoldedText {
+    /// Transforms the rope from char index `from` onwards.
+    fn new(rope: &Rope, from: usize, options: &LiteralOptions) -> Self {
+        let mut folded = Self {
+            text: String::new(),
+            bytes: Vec::new(),
+            origins: Vec::new(),
+            unit_starts: Vec::new(),
+            unit_ends: Vec::new(),
+        };
+
+        #[cfg(feature = "normalization")]
+        if let Some(form) = options.normalization {
+            for (start, end) in normalization_units(rope, from) {
+                let unit = form.apply(rope.slice(start..end).chars());
+                folded.push_unit(&unit, (start, end), options);
+            }
+            return folded;
+        }
+
+        let mut buf = [0u8; 4];
+        for (offset, c) in rope.chars_at(from).enumerate() {
+            let idx = from + offset;
+            folded.push_unit(c.encode_utf8(&mut buf), (idx, idx + 1), options);
+        }
+        folded
+    }
+
+    /// Appends the transformed text of one original char range.
+    fn push_unit(&mut self, unit: &str, origin: (usize, usize), options: &LiteralOptions) {
+        let count = unit.chars().count();
+        for (i, c) in unit.chars().enumerate() {
+            let c = if options.case_insensitive {
+                fold_case(c)
+            } else {
+                c
+            };
+            self.bytes.push(self.text.len());
+            self.origins.push(origin);
+            self.unit_starts.push(i == 0);
+            self.unit_ends.push(i + 1 == count);
+            self.text.push(c);
+        }
+    }
+
+    /// Finds every (possibly overlapping) occurrence of the transformed `needle`, as original
+    /// char ranges.
+    fn candidates(&self, needle: &str) -> Vec<(usize, usize)> {
+        let mut candidates = Vec::new();
+        if needle.is_empty() {
+            return candidates;
+        }
+        let mut search_from = 0;
+        while let Some(rel) = self.text[search_from..].find(needle) {
+            let byte_idx = search_from + rel;
+            let first = self.char_at_byte(byte_idx);
+            let last = self.char_at_byte(byte_idx + needle.len() - 1);
+            // A match must not begin or end partway through the output of one original range
+            if self.unit_starts[first] && self.unit_ends[last] {
+                candidates.push((self.origins[first].0, self.origins[last].1));
+            }
+            search_from = byte_idx + next_char_len(&self.text[byte_idx..]);
+        }
+        candidates
+    }
+
+    /// Returns the index of the transformed char containing byte offset `byte`.
+    fn char_at_byte(&self, byte: usize) -> usize {
+        match self.bytes.binary_search(&byte) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../../tests/literal_matching.rs"]
+mod literal_matching;
@@ -0,0 +1,117 @@
+use super::*;
+use crate::composer::test_helpers::{literal, setup};
+use std::fs;
+
+fn set_of(mode: ApplyMode, patches: Vec<Patch>) -> PatchSet {
+    let mut set = PatchSet::new().with_mode(mode);
+    for patch in patches {
+        set.add(patch);
+    }
+    set
+}
+
+#[test]
+fn test_sequential_patch_targets_earlier_replacement() {
+    let (_dir, file) = setup("let x = old;\n");
+    let patches = vec![
+        literal(&file, "old", "new_value"),
+        literal(&file, "new_value", "newer_value"),
+    ];
+
+    assert!(set_of(ApplyMode::Snapshot, patches.clone())
+        .apply_to_files()
+        .is_err());
+    assert_eq!(
+        set_of(ApplyMode::Sequential, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "let x = newer_value;\n"
+    );
+}
+
+#[test]
+fn test_sequential_allows_edits_within_earlier_edits() {
+    let (_dir, file) = setup("fn f() {}\n");
+    let patches = vec![
+        literal(&file, "{}", "{\n    body();\n}"),
+        literal(&file, "body()", "other()"),
+    ];
+    assert_eq!(
+        set_of(ApplyMode::Sequential, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "fn f() {\n    other();\n}\n"
+    );
+}
+
+#[test]
+fn test_sequential_follows_declaration_order() {
+    let (_dir, file) = setup("a\n");
+    let patches = vec![literal(&file, "a", "b"), literal(&file, "b", "c")];
+    assert_eq!(
+        set_of(ApplyMode::Sequential, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "c\n"
+    );
+
+    let patches = vec![literal(&file, "b", "c"), literal(&file, "a", "b")];
+    assert!(set_of(ApplyMode::Sequential, patches)
+        .apply_to_files()
+        .is_err());
+}
+
+#[test]
+fn test_sequential_defers_patch_until_dependencies_applied() {
+    let (_dir, file) = setup("a\n");
+    let patches = vec![
+        literal(&file, "b", "c").with_depends_on(["first"]),
+        literal(&file, "a", "b").with_id("first"),
+    ];
+    assert_eq!(
+        set_of(ApplyMode::Sequential, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "c\n"
+    );
+}
+
+#[test]
+fn test_sequential_inverse_restores_original() {
+    let (_dir, file) = setup("one two\n");
+    let patches = vec![
+        literal(&file, "one", "three"),
+        literal(&file, "three two", "four"),
+        literal(&file, "four", "five six"),
+    ];
+    let (results, inverse) = set_of(ApplyMode::Sequential, patches)
+        .apply_to_files_with_inverse()
+        .unwrap();
+    assert_eq!(results[&file], "five six\n");
+
+    fs::write(&file, &results[&file]).unwrap();
+    assert_eq!(inverse.apply_to_files().unwrap()[&file], "one two\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_mode_json_round_trip() {
+    let json = r#"{
+        "mode": "Sequential",
+        "line_ending": "Crlf",
+        "patches": [{"file": "a.rs", "replacement": "x"}]
+    }"#;
+    let set = PatchSet::from_json(json).unwrap();
+    assert_eq!(set.mode(), ApplyMode::Sequential);
+    assert_eq!(set.line_ending(), LineEnding::Crlf);
+    assert_eq!(set.patches().len(), 1);
+
+    let round_trip = PatchSet::from_json(&set.to_json()).unwrap();
+    assert_eq!(round_trip.mode(), ApplyMode::Sequential);
+    assert_eq!(round_trip.line_ending(), LineEnding::Crlf);
+
+    // Settings are optional, and a default set is written as a plain array
+    let set = PatchSet::from_json(r#"{"patches": []}"#).unwrap();
+    assert_eq!(set.mode(), ApplyMode::Snapshot);
+    assert!(set.to_json().starts_with('['));
+}
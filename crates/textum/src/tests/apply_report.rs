@@ -0,0 +1,140 @@
+use super::*;
+use crate::composer::test_helpers::{literal, setup};
+use crate::patch::Operation;
+use crate::snip::snippet::Snippet;
+use std::fs;
+
+fn report_of(set: &PatchSet) -> ApplyReport {
+    set.apply().unwrap().report
+}
+
+#[test]
+fn test_report_describes_each_patch() {
+    let (_dir, file) = setup("fn main() {\n    run();\n}\n");
+    let mut set = PatchSet::new();
+    set.add(literal(&file, "run()", "start()").with_id("rename"));
+    set.add(literal(&file, "main", "main"));
+
+    let report = report_of(&set);
+    assert_eq!(report.patches.len(), 2);
+
+    let rename = &report.patches[0];
+    assert_eq!(rename.index, 0);
+    assert_eq!(rename.id.as_deref(), Some("rename"));
+    assert_eq!(rename.file, file);
+    assert_eq!(rename.status, PatchStatus::Applied);
+    assert_eq!(rename.removed(), "run()");
+    assert_eq!(rename.inserted, "start()");
+    let span = rename.span.as_ref().unwrap();
+    assert_eq!((span.start.char_idx, span.end.char_idx), (16, 21));
+    assert_eq!((span.start.line, span.start.col), (1, 4));
+
+    assert_eq!(report.patches[1].status, PatchStatus::NoOp);
+}
+
+#[test]
+fn test_report_marks_skipped_patches() {
+    let (_dir, file) = setup("fn main() {}\n");
+    let mut set = PatchSet::new().with_overlap_policy(OverlapPolicy::FirstWins);
+    set.add(
+        literal(&file, "fn main", "// entry\n")
+            .with_operation(Operation::InsertBefore)
+            .idempotent(),
+    );
+    set.add(literal(&file, "main", "start"));
+    set.add(literal(&file, "main()", "run()"));
+
+    fs::write(&file, "// entry\nfn main() {}\n").unwrap();
+    let report = report_of(&set);
+    let statuses: Vec<PatchStatus> = report.patches.iter().map(|p| p.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            PatchStatus::Skipped,
+            PatchStatus::Applied,
+            PatchStatus::Skipped
+        ]
+    );
+    // The idempotent patch was skipped before it resolved; the dropped one did resolve
+    assert!(report.patches[0].span.is_none());
+    assert_eq!(report.patches[2].removed(), "main()");
+    assert_eq!(report.with_status(PatchStatus::Skipped).count(), 2);
+}
+
+#[test]
+fn test_report_counts_merged_deletions_as_applied() {
+    let (_dir, file) = setup("abcdef");
+    let mut set = PatchSet::new();
+    set.add(literal(&file, "bcd", ""));
+    set.add(literal(&file, "cde", ""));
+
+    let report = report_of(&set);
+    assert!(report
+        .patches
+        .iter()
+        .all(|p| p.status == PatchStatus::Applied));
+    assert_eq!(report.patches[1].removed(), "cde");
+}
+
+#[test]
+fn test_report_includes_file_operations() {
+    let (dir, file) = setup("x");
+    let created = dir.path().join("new.rs").to_string_lossy().to_string();
+    let mut set = PatchSet::new();
+    set.add(
+        Patch::new(created.clone(), Snippet::All, "y")
+            .with_operation(Operation::CreateFile { overwrite: false }),
+    );
+    set.add(
+        Patch::new(file.clone(), Snippet::All, "")
+            .with_operation(Operation::DeleteFile)
+            .idempotent(),
+    );
+    set.add(
+        Patch::new(
+            dir.path().join("gone.rs").to_string_lossy().to_string(),
+            Snippet::All,
+            "",
+        )
+        .with_operation(Operation::DeleteFile)
+        .idempotent(),
+    );
+
+    let report = report_of(&set);
+    let statuses: Vec<PatchStatus> = report.patches.iter().map(|p| p.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            PatchStatus::Applied,
+            PatchStatus::Applied,
+            PatchStatus::Skipped
+        ]
+    );
+    assert_eq!(report.patches[0].file, created);
+}
+
+#[test]
+fn test_report_in_sequential_mode_uses_intermediate_text() {
+    let (_dir, file) = setup("a\n");
+    let mut set = PatchSet::new().with_mode(ApplyMode::Sequential);
+    set.add(literal(&file, "a", "bb"));
+    set.add(literal(&file, "bb", "c"));
+
+    let report = report_of(&set);
+    assert_eq!(report.patches[1].removed(), "bb");
+    assert_eq!(report.patches[1].span.as_ref().unwrap().end.char_idx, 2);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_report_json() {
+    let (_dir, file) = setup("hello world\n");
+    let mut set = PatchSet::new();
+    set.add(literal(&file, "world", "rust"));
+
+    let json = report_of(&set).to_json();
+    assert!(json.contains(r#""status":"Applied""#));
+    assert!(json.contains(r#""inserted":"rust""#));
+    assert!(json.contains(r#""text":"world""#));
+    assert!(json.contains(r#""elapsed_micros":"#));
+}
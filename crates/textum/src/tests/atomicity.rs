@@ -0,0 +1,274 @@
+use super::*;
+use crate::composer::test_helpers::{literal, path};
+use crate::patch::Operation;
+use crate::snip::snippet::Snippet;
+use std::fs;
+use tempfile::TempDir;
+
+fn set_of(atomicity: Atomicity, patches: Vec<Patch>) -> PatchSet {
+    let mut set = PatchSet::new().with_atomicity(atomicity);
+    for patch in patches {
+        set.add(patch);
+    }
+    set
+}
+
+fn statuses(outcome: &Outcome) -> Vec<PatchStatus> {
+    outcome.report.patches.iter().map(|p| p.status).collect()
+}
+
+#[test]
+fn test_patch_atomicity_leaves_out_failing_patch() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "fn foo() {}\n").unwrap();
+
+    let set = set_of(
+        Atomicity::Patch,
+        vec![
+            literal(&file, "foo", "bar"),
+            literal(&file, "missing", "x").with_id("broken"),
+            literal(&file, "{}", "{ todo!() }"),
+        ],
+    );
+    assert!(set_of(Atomicity::Set, set.patches().to_vec())
+        .apply()
+        .is_err());
+
+    let outcome = set.apply().unwrap();
+    assert_eq!(
+        outcome.changes[&file],
+        FileChange::Write("fn bar() { todo!() }\n".to_string())
+    );
+    assert_eq!(
+        statuses(&outcome),
+        [
+            PatchStatus::Applied,
+            PatchStatus::Failed,
+            PatchStatus::Applied
+        ]
+    );
+    assert_eq!(outcome.failures.len(), 1);
+    assert!(matches!(
+        &outcome.failures[0],
+        PatchError::InPatch { index: 1, .. }
+    ));
+    assert!(outcome.report.patches[1]
+        .error
+        .as_deref()
+        .unwrap()
+        .starts_with("Patch 1 (\"broken\")"));
+}
+
+#[test]
+fn test_dependents_of_failed_patch_fail() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    let other = path(&dir, "b.rs");
+    fs::write(&file, "foo\n").unwrap();
+    fs::write(&other, "foo\n").unwrap();
+
+    let outcome = set_of(
+        Atomicity::Patch,
+        vec![
+            literal(&other, "foo", "baz").with_depends_on(["first"]),
+            literal(&file, "missing", "x").with_id("first"),
+        ],
+    )
+    .apply()
+    .unwrap();
+    assert_eq!(
+        statuses(&outcome),
+        [PatchStatus::Failed, PatchStatus::Failed]
+    );
+    assert!(matches!(
+        outcome.failures[0].root(),
+        PatchError::DependencyFailed { id } if id == "first"
+    ));
+    assert_eq!(
+        outcome.changes[&other],
+        FileChange::Write("foo\n".to_string())
+    );
+}
+
+#[test]
+fn test_later_overlapping_patch_fails() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "abcdef").unwrap();
+
+    let outcome = set_of(
+        Atomicity::Patch,
+        vec![
+            literal(&file, "bcd", "X"),
+            literal(&file, "cde", "Y"),
+            literal(&file, "f", "Z"),
+        ],
+    )
+    .apply()
+    .unwrap();
+    assert_eq!(
+        outcome.changes[&file],
+        FileChange::Write("aXeZ".to_string())
+    );
+    assert_eq!(
+        statuses(&outcome),
+        [
+            PatchStatus::Applied,
+            PatchStatus::Failed,
+            PatchStatus::Applied
+        ]
+    );
+    assert!(matches!(
+        outcome.failures[0],
+        PatchError::OverlappingRanges { .. }
+    ));
+}
+
+#[test]
+fn test_missing_file_fails_each_of_its_patches() {
+    let dir = TempDir::new().unwrap();
+    let missing = path(&dir, "missing.rs");
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "a").unwrap();
+
+    let outcome = set_of(
+        Atomicity::Patch,
+        vec![
+            literal(&missing, "x", "y"),
+            literal(&file, "a", "b"),
+            literal(&missing, "z", "w"),
+        ],
+    )
+    .apply()
+    .unwrap();
+    assert_eq!(
+        statuses(&outcome),
+        [
+            PatchStatus::Failed,
+            PatchStatus::Applied,
+            PatchStatus::Failed
+        ]
+    );
+    assert!(outcome
+        .failures
+        .iter()
+        .all(|e| matches!(e.root(), PatchError::FileNotFound { .. })));
+}
+
+#[test]
+fn test_file_atomicity_leaves_failing_file_unchanged() {
+    let dir = TempDir::new().unwrap();
+    let first = path(&dir, "a.rs");
+    let second = path(&dir, "b.rs");
+    fs::write(&first, "one two\n").unwrap();
+    fs::write(&second, "three\n").unwrap();
+
+    let outcome = set_of(
+        Atomicity::File,
+        vec![
+            literal(&first, "one", "1"),
+            literal(&second, "three", "3"),
+            literal(&first, "missing", "x"),
+            literal(&first, "two", "2"),
+        ],
+    )
+    .apply()
+    .unwrap();
+    assert!(!outcome.changes.contains_key(&first));
+    assert_eq!(
+        outcome.changes[&second],
+        FileChange::Write("3\n".to_string())
+    );
+    assert_eq!(
+        statuses(&outcome),
+        [
+            PatchStatus::Skipped,
+            PatchStatus::Applied,
+            PatchStatus::Failed,
+            PatchStatus::Skipped
+        ]
+    );
+    assert_eq!(outcome.inverse.patches().len(), 1);
+}
+
+#[test]
+fn test_file_atomicity_reverts_renamed_source() {
+    let dir = TempDir::new().unwrap();
+    let from = path(&dir, "old.rs");
+    let to = path(&dir, "new.rs");
+    fs::write(&from, "mod old;\n").unwrap();
+
+    let rename = Patch::new(from.clone(), Snippet::All, "")
+        .with_operation(Operation::RenameFile { to: to.clone() });
+    let outcome = set_of(Atomicity::File, vec![rename, literal(&to, "missing", "x")])
+        .apply()
+        .unwrap();
+    assert!(outcome.changes.is_empty());
+    assert!(outcome.inverse.patches().is_empty());
+    assert_eq!(
+        statuses(&outcome),
+        [PatchStatus::Skipped, PatchStatus::Failed]
+    );
+}
+
+#[test]
+fn test_file_atomicity_reverts_dependents_in_other_files() {
+    let dir = TempDir::new().unwrap();
+    let first = path(&dir, "a.rs");
+    let second = path(&dir, "b.rs");
+    let third = path(&dir, "c.rs");
+    fs::write(&first, "a\n").unwrap();
+    fs::write(&second, "b\n").unwrap();
+    fs::write(&third, "c\n").unwrap();
+
+    let outcome = set_of(
+        Atomicity::File,
+        vec![
+            literal(&first, "a", "A").with_id("base"),
+            literal(&second, "b", "B").with_depends_on(["base"]),
+            literal(&first, "missing", "x").with_depends_on(["base"]),
+            literal(&third, "c", "C"),
+        ],
+    )
+    .apply()
+    .unwrap();
+    assert_eq!(outcome.changes.keys().collect::<Vec<_>>(), [&third]);
+    assert_eq!(
+        statuses(&outcome),
+        [
+            PatchStatus::Skipped,
+            PatchStatus::Skipped,
+            PatchStatus::Failed,
+            PatchStatus::Applied
+        ]
+    );
+}
+
+#[test]
+fn test_partial_inverse_restores_original() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "let x = 1;\n").unwrap();
+
+    let set = set_of(
+        Atomicity::Patch,
+        vec![literal(&file, "x", "y"), literal(&file, "missing", "z")],
+    );
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    assert_eq!(results[&file], "let y = 1;\n");
+
+    fs::write(&file, &results[&file]).unwrap();
+    assert_eq!(inverse.apply_to_files().unwrap()[&file], "let x = 1;\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_atomicity_json() {
+    let set = PatchSet::from_json(r#"{"atomicity": "File", "patches": []}"#).unwrap();
+    assert_eq!(set.atomicity(), Atomicity::File);
+    assert_eq!(
+        PatchSet::from_json(&set.to_json()).unwrap().atomicity(),
+        Atomicity::File
+    );
+}
@@ -0,0 +1,154 @@
+use super::*;
+use crate::composer::test_helpers::{literal, path};
+use crate::{MemoryFileSystem, Patch, PatchSet};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_journal_records_hashes_and_restores_run() {
+    let dir = TempDir::new().unwrap();
+    let edited = path(&dir, "edited.txt");
+    let created = path(&dir, "created.txt");
+    fs::write(&edited, "hello world\n").unwrap();
+    let journal_dir = dir.path().join("journal");
+
+    let mut set = PatchSet::new().with_backup(Backup::Journal(journal_dir.clone()));
+    set.add(literal(&edited, "world", "rust"));
+    set.add(
+        Patch::new(created.clone(), crate::Snippet::All, "new\n")
+            .with_operation(crate::Operation::CreateFile { overwrite: false }),
+    );
+    set.write().unwrap();
+
+    let run = Journal::new(&journal_dir).last().unwrap().unwrap();
+    let entries: Vec<_> = run.files.iter().map(|e| e.file.as_str()).collect();
+    assert_eq!(entries, [created.as_str(), edited.as_str()]);
+    assert_eq!(run.files[0].original_hash, None);
+    assert_eq!(run.files[0].written_hash, Some(content_hash("new\n")));
+    assert_eq!(
+        run.files[1].original_hash,
+        Some(content_hash("hello world\n"))
+    );
+
+    run.restore().unwrap();
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "hello world\n");
+    assert!(!Path::new(&created).exists());
+    assert!(Journal::new(&journal_dir).runs().unwrap().is_empty());
+}
+
+#[test]
+fn test_restore_refuses_when_file_modified_since() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "one\n").unwrap();
+    let journal_dir = dir.path().join("journal");
+
+    let mut set = PatchSet::new().with_backup(Backup::Journal(journal_dir.clone()));
+    set.add(literal(&file, "one", "two"));
+    set.write().unwrap();
+    fs::write(&file, "edited by hand\n").unwrap();
+
+    let run = Journal::new(&journal_dir).last().unwrap().unwrap();
+    let err = run.restore().unwrap_err();
+    assert!(matches!(err, PatchError::FileChanged { file: f } if f == file));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "edited by hand\n");
+    assert_eq!(Journal::new(&journal_dir).runs().unwrap().len(), 1);
+}
+
+#[test]
+fn test_runs_restore_newest_first() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "v1\n").unwrap();
+    let journal = Journal::new(dir.path().join("journal"));
+
+    for (from, to) in [("v1", "v2"), ("v2", "v3")] {
+        let mut set = PatchSet::new().with_backup(Backup::Journal(dir.path().join("journal")));
+        set.add(literal(&file, from, to));
+        set.write().unwrap();
+    }
+    assert_eq!(journal.runs().unwrap().len(), 2);
+
+    journal.last().unwrap().unwrap().restore().unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "v2\n");
+    journal.last().unwrap().unwrap().restore().unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "v1\n");
+}
+
+#[test]
+fn test_suffix_backup_keeps_deleted_file() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "gone.txt");
+    fs::write(&file, "keep me\n").unwrap();
+
+    let mut set = PatchSet::new().with_backup(Backup::Suffix);
+    set.add(
+        Patch::new(file.clone(), crate::Snippet::All, "")
+            .with_operation(crate::Operation::DeleteFile),
+    );
+    set.write().unwrap();
+
+    assert!(!Path::new(&file).exists());
+    assert_eq!(
+        fs::read_to_string(format!("{file}.orig")).unwrap(),
+        "keep me\n"
+    );
+}
+
+#[test]
+fn test_backups_and_restore_stay_in_file_system() {
+    let files = Arc::new(
+        MemoryFileSystem::new()
+            .with_file("a.txt", "one\n")
+            .with_file("b.txt", "keep\n"),
+    );
+
+    let mut set = PatchSet::new()
+        .with_file_system(files.clone())
+        .with_backup(Backup::Suffix);
+    set.add(literal("b.txt", "keep", "kept"));
+    set.write().unwrap();
+    assert_eq!(files.get("b.txt.orig").as_deref(), Some("keep\n"));
+
+    let mut set = PatchSet::new()
+        .with_file_system(files.clone())
+        .with_backup(Backup::Journal("journal".into()));
+    set.add(literal("a.txt", "one", "two"));
+    let outcome = set.write().unwrap();
+    assert_eq!(outcome.changes.len(), 1);
+    assert_eq!(files.get("a.txt").as_deref(), Some("two\n"));
+
+    let journal = Journal::new("journal").with_file_system(files.clone());
+    assert!(Journal::new("journal").runs().unwrap().is_empty());
+    journal.last().unwrap().unwrap().restore().unwrap();
+    assert_eq!(files.get("a.txt").as_deref(), Some("one\n"));
+    assert!(journal.runs().unwrap().is_empty());
+    let names: Vec<String> = files.files().into_keys().collect();
+    assert_eq!(names, ["a.txt", "b.txt", "b.txt.orig", "journal/index"]);
+}
+
+#[test]
+fn test_suffix_backup_refuses_to_replace_existing_orig() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "one\n").unwrap();
+
+    let mut set = PatchSet::new().with_backup(Backup::Suffix);
+    set.add(literal(&file, "one", "two"));
+    set.write().unwrap();
+
+    let mut set = PatchSet::new().with_backup(Backup::Suffix);
+    set.add(literal(&file, "two", "three"));
+    let err = set.write().unwrap_err();
+    assert!(matches!(err, PatchError::FileExists { file: f } if f == format!("{file}.orig")));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "two\n");
+    assert_eq!(fs::read_to_string(format!("{file}.orig")).unwrap(), "one\n");
+}
+
+#[test]
+fn test_escaped_paths_round_trip() {
+    for file in ["plain.txt", "with space.txt", "back\\slash", "new\nline"] {
+        assert_eq!(unescape(&escape(file)).as_deref(), Some(file));
+    }
+    assert_eq!(unescape("trailing\\"), None);
+}
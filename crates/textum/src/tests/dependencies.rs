@@ -0,0 +1,149 @@
+use super::*;
+use crate::composer::test_helpers::{literal, setup};
+use std::fs;
+
+fn set_of(patches: Vec<Patch>) -> PatchSet {
+    let mut set = PatchSet::new();
+    for patch in patches {
+        set.add(patch);
+    }
+    set
+}
+
+#[test]
+fn test_error_cites_patch_id_and_file() {
+    let (_dir, file) = setup("fn main() {}\n");
+    let set = set_of(vec![
+        literal(&file, "main", "start"),
+        literal(&file, "absent", "x").with_id("second"),
+    ]);
+
+    let err = set.apply_to_files().unwrap_err();
+    match &err {
+        PatchError::InPatch {
+            index, id, file: f, ..
+        } => {
+            assert_eq!(*index, 1);
+            assert_eq!(id.as_deref(), Some("second"));
+            assert_eq!(*f, file);
+        }
+        other => panic!("expected InPatch, got {other:?}"),
+    }
+    assert!(matches!(err.root(), PatchError::SnippetError(_)));
+    assert!(err
+        .to_string()
+        .starts_with(&format!("Patch 1 (\"second\") in {file}: ")));
+}
+
+#[test]
+fn test_error_without_id_cites_index() {
+    let (_dir, file) = setup("fn main() {}\n");
+    let err = set_of(vec![literal(&file, "absent", "x")])
+        .apply_to_files()
+        .unwrap_err();
+    assert!(err.to_string().starts_with(&format!("Patch 0 in {file}: ")));
+}
+
+#[test]
+fn test_dependent_patch_sees_edited_text() {
+    let (_dir, file) = setup("fn foo() {}\nfoo();\n");
+    let set = set_of(vec![
+        literal(&file, "bar();", "bar(1);").with_depends_on(["rename-call"]),
+        literal(&file, "foo();", "bar();").with_id("rename-call"),
+        literal(&file, "fn foo", "fn bar"),
+    ]);
+    assert_eq!(
+        set.apply_to_files().unwrap()[&file],
+        "fn bar() {}\nbar(1);\n"
+    );
+}
+
+#[test]
+fn test_without_dependency_patch_sees_original_text() {
+    let (_dir, file) = setup("foo();\n");
+    let set = set_of(vec![
+        literal(&file, "foo();", "bar();"),
+        literal(&file, "bar();", "bar(1);"),
+    ]);
+    assert!(matches!(
+        set.apply_to_files().as_ref().map_err(PatchError::root),
+        Err(PatchError::SnippetError(_))
+    ));
+}
+
+#[test]
+fn test_dependency_chain() {
+    let (_dir, file) = setup("a\n");
+    let set = set_of(vec![
+        literal(&file, "c", "d").with_depends_on(["b"]),
+        literal(&file, "b", "c").with_id("b").with_depends_on(["a"]),
+        literal(&file, "a", "b").with_id("a"),
+    ]);
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    assert_eq!(results[&file], "d\n");
+
+    fs::write(&file, &results[&file]).unwrap();
+    assert_eq!(inverse.apply_to_files().unwrap()[&file], "a\n");
+}
+
+#[test]
+fn test_unknown_dependency() {
+    let (_dir, file) = setup("a\n");
+    let err = set_of(vec![literal(&file, "a", "b").with_depends_on(["missing"])])
+        .apply_to_files()
+        .unwrap_err();
+    assert!(matches!(err, PatchError::InPatch { index: 0, .. }));
+    assert!(matches!(err.root(), PatchError::UnknownDependency { id } if id == "missing"));
+}
+
+#[test]
+fn test_duplicate_id() {
+    let (_dir, file) = setup("a b\n");
+    let err = set_of(vec![
+        literal(&file, "a", "x").with_id("same"),
+        literal(&file, "b", "y").with_id("same"),
+    ])
+    .apply_to_files()
+    .unwrap_err();
+    assert!(matches!(err, PatchError::InPatch { index: 1, .. }));
+    assert!(matches!(err.root(), PatchError::DuplicateId { id } if id == "same"));
+}
+
+#[test]
+fn test_dependency_cycle() {
+    let (_dir, file) = setup("a b\n");
+    let err = set_of(vec![
+        literal(&file, "a", "x")
+            .with_id("one")
+            .with_depends_on(["two"]),
+        literal(&file, "b", "y")
+            .with_id("two")
+            .with_depends_on(["one"]),
+    ])
+    .apply_to_files()
+    .unwrap_err();
+    match err {
+        PatchError::DependencyCycle { ids } => assert_eq!(ids, ["one", "two"]),
+        other => panic!("expected DependencyCycle, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_identity_json() {
+    let json = r#"[{
+        "id": "p1",
+        "description": "Rename the entry point",
+        "depends_on": ["p0"],
+        "file": "a.rs",
+        "snippet": "All",
+        "replacement": "x"
+    }]"#;
+    let parsed: Vec<Patch> = facet_json::from_str(json).unwrap();
+    assert_eq!(parsed[0].id.as_deref(), Some("p1"));
+    assert_eq!(
+        parsed[0].description.as_deref(),
+        Some("Rename the entry point")
+    );
+    assert_eq!(parsed[0].depends_on, ["p0"]);
+}
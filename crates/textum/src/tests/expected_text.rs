@@ -0,0 +1,118 @@
+use super::*;
+use crate::composer::PatchSet;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_expected_text_matches() {
+    let mut rope = Rope::from_str("let x = 1;");
+    let patch = Patch::from_literal_target("a.rs".to_string(), "1", BoundaryMode::Include, "2")
+        .with_expected(Expected::Text("1".to_string()));
+
+    patch.apply(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), "let x = 2;");
+}
+
+#[test]
+fn test_expected_text_mismatch_reports_actual() {
+    let mut rope = Rope::from_str("a\nb\nc\n");
+    let patch = Patch::from_line_range("a.txt".to_string(), 1, 2, "B\n")
+        .with_expected(Expected::Text("x\n".to_string()));
+
+    match patch.apply(&mut rope) {
+        Err(PatchError::ExpectedMismatch {
+            file,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(file, "a.txt");
+            assert_eq!(expected, Expected::Text("x\n".to_string()));
+            assert_eq!(actual, "b\n");
+        }
+        other => panic!("expected ExpectedMismatch, got {other:?}"),
+    }
+    assert_eq!(rope.to_string(), "a\nb\nc\n");
+}
+
+#[test]
+fn test_expected_hash() {
+    let rope = Rope::from_str("fn main() {}");
+    let patch =
+        Patch::from_literal_target("a.rs".to_string(), "main", BoundaryMode::Include, "run");
+
+    let ok = patch.clone().with_expected(Expected::hash_of("main"));
+    assert!(ok.resolve(&rope).is_ok());
+
+    let upper = Expected::Hash(content_hash("main").to_uppercase());
+    assert!(patch.clone().with_expected(upper).resolve(&rope).is_ok());
+
+    let stale = patch.with_expected(Expected::hash_of("start"));
+    assert!(matches!(
+        stale.resolve(&rope),
+        Err(PatchError::ExpectedMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_expected_checks_snippet_range_for_insertions() {
+    // Insertions check the range they are anchored to, not the empty insertion point
+    let rope = Rope::from_str("use std::fs;\n");
+    let patch = Patch::from_line_range("a.rs".to_string(), 0, 1, "use std::io;\n")
+        .with_operation(Operation::InsertAfter)
+        .with_expected(Expected::Text("use std::fs;\n".to_string()));
+
+    assert_eq!(
+        patch.resolve(&rope).unwrap(),
+        Some(Edit::new(13, 13, "use std::io;\n"))
+    );
+}
+
+#[test]
+fn test_expected_checks_whole_file_for_file_level_operations() {
+    let rope = Rope::from_str("body\n");
+    let patch = Patch::new("a.txt".to_string(), Snippet::All, "tail\n")
+        .with_operation(Operation::Append)
+        .with_expected(Expected::Text("other\n".to_string()));
+
+    assert!(matches!(
+        patch.resolve(&rope),
+        Err(PatchError::ExpectedMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_patchset_rejects_stale_patch_without_partial_changes() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("stale.txt");
+    fs::write(&file, "alpha beta\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(Patch::from_literal_target(
+        file.clone(),
+        "alpha",
+        BoundaryMode::Include,
+        "ALPHA",
+    ));
+    set.add(
+        Patch::from_literal_target(file.clone(), "beta", BoundaryMode::Include, "BETA")
+            .with_expected(Expected::Text("gamma".to_string())),
+    );
+
+    let err = set.apply_to_files().unwrap_err();
+    assert!(err.to_string().contains("found \"beta\""));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "alpha beta\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_expected_json() {
+    let json = r#"[{
+        "file": "a.txt",
+        "snippet": "All",
+        "replacement": "x",
+        "expected": {"Hash": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"}
+    }]"#;
+    let parsed: Vec<Patch> = facet_json::from_str(json).unwrap();
+    assert_eq!(parsed[0].expected, Some(Expected::hash_of("abc")));
+}
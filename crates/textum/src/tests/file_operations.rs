@@ -0,0 +1,280 @@
+use super::*;
+use crate::composer::test_helpers::path;
+use crate::patch::{Expected, Operation};
+use crate::snip::snippet::{BoundaryMode, Snippet};
+use std::fs;
+use tempfile::TempDir;
+
+fn file_op(file: &str, operation: Operation, content: &str) -> Patch {
+    Patch::new(file.to_string(), Snippet::All, content).with_operation(operation)
+}
+
+fn create(file: &str, content: &str) -> Patch {
+    file_op(file, Operation::CreateFile { overwrite: false }, content)
+}
+
+fn write_changes(changes: HashMap<String, FileChange>) {
+    for (file, change) in changes {
+        match change {
+            FileChange::Write(content) => fs::write(file, content).unwrap(),
+            FileChange::Delete => fs::remove_file(file).unwrap(),
+        }
+    }
+}
+
+fn set_of(patches: Vec<Patch>) -> PatchSet {
+    let mut set = PatchSet::new();
+    for patch in patches {
+        set.add(patch);
+    }
+    set
+}
+
+#[test]
+fn test_create_file() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "new.rs");
+
+    let changes = set_of(vec![create(&file, "fn f() {}\n")])
+        .apply_to_changes()
+        .unwrap();
+    assert_eq!(changes[&file], FileChange::Write("fn f() {}\n".to_string()));
+}
+
+#[test]
+fn test_create_file_writes_placeholder_as_given() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "hello.hbs");
+
+    let changes = set_of(vec![create(&file, "Hi {{match}}!\n")])
+        .apply_to_changes()
+        .unwrap();
+    assert_eq!(
+        changes[&file],
+        FileChange::Write("Hi {{match}}!\n".to_string())
+    );
+}
+
+#[test]
+fn test_create_existing_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "old").unwrap();
+
+    let err = set_of(vec![create(&file, "new")])
+        .apply_to_changes()
+        .unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileExists { file: f } if *f == file));
+}
+
+#[test]
+fn test_create_with_overwrite() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "old").unwrap();
+
+    let patch = file_op(&file, Operation::CreateFile { overwrite: true }, "new");
+    let results = set_of(vec![patch]).apply_to_files().unwrap();
+    assert_eq!(results[&file], "new");
+}
+
+#[test]
+fn test_idempotent_create_skips_identical_file() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, "same").unwrap();
+
+    let results = set_of(vec![create(&file, "same").idempotent()])
+        .apply_to_files()
+        .unwrap();
+    assert!(!results.contains_key(&file));
+}
+
+#[test]
+fn test_created_file_can_be_edited() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "mod.rs");
+
+    let edit = Patch::from_literal_target(file.clone(), "todo", BoundaryMode::Include, "done");
+    let results = set_of(vec![edit, create(&file, "// todo\n")])
+        .apply_to_files()
+        .unwrap();
+    assert_eq!(results[&file], "// done\n");
+}
+
+#[test]
+fn test_delete_file() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "gone.rs");
+    fs::write(&file, "x").unwrap();
+
+    let changes = set_of(vec![file_op(&file, Operation::DeleteFile, "")])
+        .apply_to_changes()
+        .unwrap();
+    assert_eq!(changes[&file], FileChange::Delete);
+    assert!(set_of(vec![file_op(&file, Operation::DeleteFile, "")])
+        .apply_to_files()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_delete_guarded_by_expected() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "gone.rs");
+    fs::write(&file, "changed").unwrap();
+
+    let patch = file_op(&file, Operation::DeleteFile, "")
+        .with_expected(Expected::Text("original".to_string()));
+    let err = set_of(vec![patch]).apply_to_changes().unwrap_err();
+    assert!(matches!(err.root(), PatchError::ExpectedMismatch { .. }));
+}
+
+#[test]
+fn test_delete_missing_file() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "missing.rs");
+
+    let err = set_of(vec![file_op(&file, Operation::DeleteFile, "")])
+        .apply_to_changes()
+        .unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileNotFound { .. }));
+
+    let patch = file_op(&file, Operation::DeleteFile, "").idempotent();
+    assert!(set_of(vec![patch]).apply_to_changes().unwrap().is_empty());
+}
+
+#[test]
+fn test_edit_deleted_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "gone.rs");
+    fs::write(&file, "x").unwrap();
+
+    let edit = Patch::new(file.clone(), Snippet::All, "y");
+    let err = set_of(vec![file_op(&file, Operation::DeleteFile, ""), edit])
+        .apply_to_changes()
+        .unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileNotFound { file: f } if *f == file));
+}
+
+#[test]
+fn test_rename_then_edit_destination() {
+    let dir = TempDir::new().unwrap();
+    let from = path(&dir, "old.rs");
+    let to = path(&dir, "new.rs");
+    fs::write(&from, "mod old;\n").unwrap();
+
+    let rename = file_op(&from, Operation::RenameFile { to: to.clone() }, "");
+    let edit = Patch::from_literal_target(to.clone(), "old", BoundaryMode::Include, "new");
+    let changes = set_of(vec![rename, edit]).apply_to_changes().unwrap();
+    assert_eq!(changes[&from], FileChange::Delete);
+    assert_eq!(changes[&to], FileChange::Write("mod new;\n".to_string()));
+}
+
+#[test]
+fn test_rename_onto_existing_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let from = path(&dir, "a.rs");
+    let to = path(&dir, "b.rs");
+    fs::write(&from, "a").unwrap();
+    fs::write(&to, "b").unwrap();
+
+    let rename = file_op(&from, Operation::RenameFile { to: to.clone() }, "");
+    let err = set_of(vec![rename]).apply_to_changes().unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileExists { file: f } if *f == to));
+}
+
+#[test]
+fn test_failure_leaves_no_changes() {
+    let dir = TempDir::new().unwrap();
+    let created = path(&dir, "created.rs");
+    let edited = path(&dir, "edited.rs");
+    fs::write(&edited, "content").unwrap();
+
+    let bad = Patch::from_literal_target(edited.clone(), "absent", BoundaryMode::Include, "x");
+    assert!(set_of(vec![create(&created, "x"), bad])
+        .apply_to_changes()
+        .is_err());
+    assert!(!fs::exists(&created).unwrap());
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "content");
+}
+
+#[test]
+fn test_inverse_restores_file_operations() {
+    let dir = TempDir::new().unwrap();
+    let created = path(&dir, "created.rs");
+    let deleted = path(&dir, "deleted.rs");
+    let from = path(&dir, "from.rs");
+    let to = path(&dir, "to.rs");
+    let edited = path(&dir, "edited.rs");
+    fs::write(&deleted, "deleted\n").unwrap();
+    fs::write(&from, "moved\n").unwrap();
+    fs::write(&edited, "before\n").unwrap();
+
+    let set = set_of(vec![
+        create(&created, "created\n"),
+        file_op(&deleted, Operation::DeleteFile, ""),
+        file_op(&from, Operation::RenameFile { to: to.clone() }, ""),
+        Patch::from_literal_target(to.clone(), "moved", BoundaryMode::Include, "edited"),
+        Patch::from_literal_target(edited.clone(), "before", BoundaryMode::Include, "after"),
+    ]);
+    let (changes, inverse) = set.apply_to_changes_with_inverse().unwrap();
+    write_changes(changes);
+    assert_eq!(fs::read_to_string(&to).unwrap(), "edited\n");
+
+    write_changes(inverse.apply_to_changes().unwrap());
+    assert!(!fs::exists(&created).unwrap());
+    assert!(!fs::exists(&to).unwrap());
+    assert_eq!(fs::read_to_string(&deleted).unwrap(), "deleted\n");
+    assert_eq!(fs::read_to_string(&from).unwrap(), "moved\n");
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "before\n");
+}
+
+#[test]
+fn test_inverse_restores_files_holding_placeholders() {
+    let dir = TempDir::new().unwrap();
+    let deleted = path(&dir, "deleted.hbs");
+    let replaced = path(&dir, "replaced.hbs");
+    fs::write(&deleted, "<p>{{match}}</p>\n").unwrap();
+    fs::write(&replaced, "\\{{match}}\n").unwrap();
+
+    let set = set_of(vec![
+        file_op(&deleted, Operation::DeleteFile, ""),
+        file_op(
+            &replaced,
+            Operation::CreateFile { overwrite: true },
+            "new\n",
+        ),
+    ]);
+    let (changes, inverse) = set.apply_to_changes_with_inverse().unwrap();
+    write_changes(changes);
+
+    write_changes(inverse.apply_to_changes().unwrap());
+    assert_eq!(fs::read_to_string(&deleted).unwrap(), "<p>{{match}}</p>\n");
+    assert_eq!(fs::read_to_string(&replaced).unwrap(), "\\{{match}}\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_file_operations_json() {
+    let json = r#"[
+        {"file": "a.rs", "operation": {"CreateFile": {}}, "replacement": "fn a() {}\n"},
+        {"file": "b.rs", "operation": {"CreateFile": {"overwrite": true}}},
+        {"file": "c.rs", "operation": "DeleteFile"},
+        {"file": "d.rs", "operation": {"RenameFile": {"to": "e.rs"}}}
+    ]"#;
+    let set = PatchSet::from_json(json).unwrap();
+    let operations: Vec<&Operation> = set.patches().iter().map(|p| &p.operation).collect();
+    assert_eq!(
+        operations,
+        [
+            &Operation::CreateFile { overwrite: false },
+            &Operation::CreateFile { overwrite: true },
+            &Operation::DeleteFile,
+            &Operation::RenameFile {
+                to: "e.rs".to_string()
+            },
+        ]
+    );
+    assert_eq!(set.patches()[0].snippet, Snippet::All);
+}
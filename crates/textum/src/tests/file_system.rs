@@ -0,0 +1,69 @@
+use super::*;
+use crate::composer::test_helpers::literal;
+use crate::snip::snippet::Snippet;
+use crate::{Operation, Patch, PatchSet};
+use std::sync::Arc;
+
+#[test]
+fn test_memory_file_system_applies_file_operations() {
+    let files = Arc::new(
+        MemoryFileSystem::new()
+            .with_file("src/old.rs", "fn old() {}\n")
+            .with_file("gone.txt", "bye\n"),
+    );
+
+    let mut set = PatchSet::new().with_file_system(files.clone());
+    set.add(literal("src/new.rs", "old", "new"));
+    set.add(
+        Patch::new("src/old.rs".to_string(), Snippet::All, "").with_operation(
+            Operation::RenameFile {
+                to: "src/new.rs".to_string(),
+            },
+        ),
+    );
+    set.add(
+        Patch::new("gone.txt".to_string(), Snippet::All, "").with_operation(Operation::DeleteFile),
+    );
+    set.write().unwrap();
+
+    assert_eq!(
+        files.files(),
+        BTreeMap::from([("src/new.rs".to_string(), "fn new() {}\n".to_string())])
+    );
+}
+
+#[test]
+fn test_memory_file_system_detects_change_after_read() {
+    let files = Arc::new(MemoryFileSystem::new().with_file("a.txt", "hello world\n"));
+
+    let mut set = PatchSet::new().with_file_system(files.clone());
+    set.add(literal("a.txt", "world", "rust"));
+    let outcome = set.apply().unwrap();
+
+    files.insert("a.txt", "hello there\n");
+    let err = outcome.verify().unwrap_err();
+    assert!(matches!(err, PatchError::FileChanged { file } if file == "a.txt"));
+}
+
+#[test]
+fn test_memory_commit_is_all_or_nothing() {
+    let files = MemoryFileSystem::new().with_file("a.txt", "a");
+    let changes = HashMap::from([
+        ("a.txt".to_string(), FileChange::Write("b".to_string())),
+        ("missing.txt".to_string(), FileChange::Delete),
+    ]);
+
+    let err = files.commit(&changes).unwrap_err();
+    assert!(matches!(err, PatchError::WriteFailed { file, .. } if file == "missing.txt"));
+    assert_eq!(files.get("a.txt").unwrap(), "a");
+}
+
+#[test]
+fn test_missing_file_is_not_found_in_memory() {
+    let files = Arc::new(MemoryFileSystem::new());
+
+    let mut set = PatchSet::new().with_file_system(files);
+    set.add(literal("absent.txt", "x", "y"));
+    let err = set.apply().unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileNotFound { .. }));
+}
@@ -0,0 +1,24 @@
+//! Fixtures shared by the patch set tests.
+
+use crate::snip::snippet::BoundaryMode;
+use crate::Patch;
+use std::fs;
+use tempfile::TempDir;
+
+/// Writes `content` to `a.rs` in a new temporary directory, returning both.
+pub(crate) fn setup(content: &str) -> (TempDir, String) {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.rs");
+    fs::write(&file, content).unwrap();
+    (dir, file)
+}
+
+/// Returns the path of `name` in `dir`.
+pub(crate) fn path(dir: &TempDir, name: &str) -> String {
+    dir.path().join(name).to_string_lossy().to_string()
+}
+
+/// Replaces the first `needle` in `file` with `replacement`.
+pub(crate) fn literal(file: &str, needle: &str, replacement: &str) -> Patch {
+    Patch::from_literal_target(file.to_string(), needle, BoundaryMode::Include, replacement)
+}
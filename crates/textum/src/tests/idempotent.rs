@@ -0,0 +1,168 @@
+use super::*;
+use crate::composer::PatchSet;
+use std::fs;
+use tempfile::TempDir;
+
+fn twice(content: &str, patch: &Patch) -> (PatchStatus, PatchStatus, String) {
+    let mut rope = Rope::from_str(content);
+    let first = patch.apply(&mut rope).unwrap();
+    let second = patch.apply(&mut rope).unwrap();
+    (first, second, rope.to_string())
+}
+
+fn literal(needle: &str, replacement: &str, operation: Operation) -> Patch {
+    Patch::from_literal_target(
+        "a.rs".to_string(),
+        needle,
+        BoundaryMode::Include,
+        replacement,
+    )
+    .with_operation(operation)
+    .idempotent()
+}
+
+#[test]
+fn test_insert_before_skips_second_run() {
+    let patch = literal("fn main", "#[inline]\n", Operation::InsertBefore);
+    assert_eq!(
+        twice("fn main() {}\n", &patch),
+        (
+            PatchStatus::Applied,
+            PatchStatus::Skipped,
+            "#[inline]\nfn main() {}\n".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_insert_after_skips_second_run() {
+    let patch = literal("use std::fs;\n", "use std::io;\n", Operation::InsertAfter);
+    let (_, second, text) = twice("use std::fs;\n", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "use std::fs;\nuse std::io;\n");
+}
+
+#[test]
+fn test_replace_skips_when_target_absent_but_replacement_present() {
+    // The rename removed the target, so a second run would otherwise fail with NotFound
+    let patch = literal("old_name", "new_name", Operation::Replace);
+    let (_, second, text) = twice("let old_name = 1;", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "let new_name = 1;");
+}
+
+#[test]
+fn test_replace_containing_target_is_not_reapplied() {
+    // The target still matches after the replacement, inside the replacement itself
+    let patch = literal("value", "value.clone()", Operation::Replace);
+    let (_, second, text) = twice("let x = value;", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "let x = value.clone();");
+}
+
+#[test]
+fn test_delete_skips_when_target_gone() {
+    let patch = literal("dbg!(x);\n", "", Operation::Delete);
+    let (_, second, text) = twice("dbg!(x);\nrun();\n", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "run();\n");
+}
+
+#[test]
+fn test_wrap_skips_second_run() {
+    let patch = literal(
+        "x",
+        "",
+        Operation::Wrap {
+            prefix: "Some(".to_string(),
+            suffix: ")".to_string(),
+        },
+    );
+    let (_, second, text) = twice("let y = x;", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "let y = Some(x);");
+}
+
+#[test]
+fn test_append_and_prepend_skip_second_run() {
+    let append = Patch::new("a.txt".to_string(), Snippet::All, "tail\n")
+        .with_operation(Operation::Append)
+        .idempotent();
+    let prepend = Patch::new("a.txt".to_string(), Snippet::All, "head\n")
+        .with_operation(Operation::Prepend)
+        .idempotent();
+
+    assert_eq!(twice("body\n", &append).2, "body\ntail\n");
+    assert_eq!(twice("body\n", &prepend).2, "head\nbody\n");
+}
+
+#[test]
+fn test_missing_target_without_replacement_still_errors() {
+    let mut rope = Rope::from_str("nothing here");
+    let patch = literal("absent", "present", Operation::Replace);
+    assert!(matches!(
+        patch.apply(&mut rope),
+        Err(PatchError::SnippetError(_))
+    ));
+}
+
+#[test]
+fn test_non_idempotent_patch_reapplies() {
+    let mut rope = Rope::from_str("fn main() {}");
+    let patch = Patch::from_literal_target("a.rs".to_string(), "fn", BoundaryMode::Include, "pub ")
+        .with_operation(Operation::InsertBefore);
+
+    patch.apply(&mut rope).unwrap();
+    patch.apply(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), "pub pub fn main() {}");
+}
+
+#[test]
+fn test_patchset_rerun_is_noop() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("gen.rs");
+    fs::write(&file, "mod a;\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(
+        Patch::from_literal_target(file.clone(), "mod a;\n", BoundaryMode::Include, "mod b;\n")
+            .with_operation(Operation::InsertAfter)
+            .with_expected(Expected::Text("mod a;\n".to_string()))
+            .idempotent(),
+    );
+
+    for _ in 0..2 {
+        let results = set.apply_to_files().unwrap();
+        fs::write(&file, results.get(&file).unwrap()).unwrap();
+    }
+    assert_eq!(fs::read_to_string(&file).unwrap(), "mod a;\nmod b;\n");
+}
+
+#[test]
+fn test_already_applied_patch_skips_match_count_check() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("renamed.rs");
+    fs::write(&file, "let renamed = 1;\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let boundary = Boundary::new(
+        Target::Literal("original".to_string()),
+        BoundaryMode::Include,
+    )
+    .with_expected_count(crate::MatchCount::Exactly(1));
+    let mut set = PatchSet::new();
+    set.add(Patch::new(file.clone(), Snippet::At(boundary), "renamed").idempotent());
+
+    let results = set.apply_to_files().unwrap();
+    assert_eq!(results.get(&file).unwrap(), "let renamed = 1;\n");
+}
+
+#[test]
+fn test_replace_embedding_target_skips_second_run() {
+    // The target reappears after a prefix inside its replacement
+    let patch = literal("count", "self.count", Operation::Replace);
+    let (_, second, text) = twice("count += 1;", &patch);
+    assert_eq!(second, PatchStatus::Skipped);
+    assert_eq!(text, "self.count += 1;");
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::composer::test_helpers::literal;
+use crate::patch::Operation;
+use crate::snip::snippet::Snippet;
+
+fn map_of(files: &[(&str, &str)]) -> HashMap<String, Rope> {
+    files
+        .iter()
+        .map(|(file, content)| ((*file).to_string(), Rope::from_str(content)))
+        .collect()
+}
+
+#[test]
+fn test_apply_to_map_handles_file_operations() {
+    let mut set = PatchSet::new();
+    set.add(
+        Patch::new("old.rs".to_string(), Snippet::All, "").with_operation(Operation::RenameFile {
+            to: "new.rs".to_string(),
+        }),
+    );
+    set.add(
+        Patch::new("created.rs".to_string(), Snippet::All, "// new\n")
+            .with_operation(Operation::CreateFile { overwrite: false }),
+    );
+    set.add(literal("new.rs", "old", "new"));
+
+    let patched = set
+        .apply_to_map(map_of(&[("old.rs", "fn old() {}\n")]))
+        .unwrap();
+
+    let mut files: Vec<(&str, String)> = patched
+        .iter()
+        .map(|(file, rope)| (file.as_str(), rope.to_string()))
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            ("created.rs", "// new\n".to_string()),
+            ("new.rs", "fn new() {}\n".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_apply_to_map_checks_overlaps() {
+    let mut set = PatchSet::new();
+    set.add(literal("a.txt", "abc", "x"));
+    set.add(literal("a.txt", "bcd", "y"));
+
+    let err = set.apply_to_map(map_of(&[("a.txt", "abcde")])).unwrap_err();
+    assert!(matches!(err, PatchError::OverlappingRanges { .. }));
+
+    let set = PatchSet {
+        overlap_policy: OverlapPolicy::FirstWins,
+        ..set
+    };
+    let patched = set.apply_to_map(map_of(&[("a.txt", "abcde")])).unwrap();
+    assert_eq!(patched["a.txt"].to_string(), "xde");
+}
+
+#[test]
+fn test_apply_to_map_returns_first_failure() {
+    let mut set = PatchSet::new().with_atomicity(Atomicity::Patch);
+    set.add(literal("a.txt", "one", "uno"));
+    set.add(literal("a.txt", "missing", "x"));
+    set.add(literal("b.txt", "also missing", "y"));
+
+    let files = map_of(&[("a.txt", "one two\n"), ("b.txt", "three\n")]);
+    let err = set.apply_to_map(files.clone()).unwrap_err();
+    assert!(matches!(err, PatchError::InPatch { index: 1, .. }));
+    let err = set.apply_to_rope(&files["a.txt"]).unwrap_err();
+    assert!(matches!(err, PatchError::InPatch { index: 1, .. }));
+}
+
+#[test]
+fn test_apply_to_map_keeps_unnamed_files() {
+    let mut set = PatchSet::new();
+    set.add(literal("a.txt", "one", "uno"));
+
+    let files = map_of(&[("a.txt", "one\n"), ("b.txt", "two\n"), ("c.txt", "three\n")]);
+    let patched = set.apply_to_map(files).unwrap();
+    assert_eq!(patched.len(), 3);
+    assert_eq!(patched["a.txt"].to_string(), "uno\n");
+    assert_eq!(patched["c.txt"].to_string(), "three\n");
+}
+
+#[test]
+fn test_apply_to_map_reports_missing_file() {
+    let mut set = PatchSet::new();
+    set.add(literal("absent.txt", "x", "y"));
+
+    let err = set.apply_to_map(HashMap::new()).unwrap_err();
+    assert!(matches!(err.root(), PatchError::FileNotFound { file } if file == "absent.txt"));
+}
+
+#[test]
+fn test_apply_to_rope_ignores_file_names() {
+    let mut set = PatchSet::new();
+    set.add(literal("one", "hello", "goodbye"));
+    set.add(literal("two", "world", "moon"));
+
+    let patched = set.apply_to_rope(&Rope::from_str("hello world")).unwrap();
+    assert_eq!(patched.to_string(), "goodbye moon");
+    assert_eq!(
+        PatchSet::new()
+            .apply_to_rope(&Rope::from_str("same"))
+            .unwrap(),
+        "same"
+    );
+}
+
+#[test]
+fn test_apply_to_rope_fails_when_text_is_deleted() {
+    let mut set = PatchSet::new();
+    set.add(Patch::new("doc".to_string(), Snippet::All, "").with_operation(Operation::DeleteFile));
+
+    let err = set.apply_to_rope(&Rope::from_str("text")).unwrap_err();
+    assert!(matches!(err, PatchError::FileNotFound { file } if file == "doc"));
+}
@@ -0,0 +1,71 @@
+use super::*;
+use crate::composer::test_helpers::setup;
+use crate::patch::Operation;
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::Target;
+
+fn insert_at(file: &str, index: usize, text: &str) -> Patch {
+    let boundary = Boundary::new(Target::Char(index), BoundaryMode::Include);
+    Patch::new(file.to_string(), Snippet::At(boundary), text)
+        .with_operation(Operation::InsertBefore)
+}
+
+fn apply(file: &str, patches: Vec<Patch>) -> String {
+    let mut set = PatchSet::new();
+    for patch in patches {
+        set.add(patch);
+    }
+    set.apply_to_files().unwrap().remove(file).unwrap()
+}
+
+#[test]
+fn test_insertions_keep_declaration_order() {
+    let (_dir, file) = setup("xy");
+    let patches = vec![
+        insert_at(&file, 1, "a"),
+        insert_at(&file, 1, "b"),
+        insert_at(&file, 1, "c"),
+    ];
+    assert_eq!(apply(&file, patches), "xabcy");
+}
+
+#[test]
+fn test_priority_places_insertion_first() {
+    let (_dir, file) = setup("xy");
+    let patches = vec![
+        insert_at(&file, 1, "a"),
+        insert_at(&file, 1, "b").with_priority(1),
+        insert_at(&file, 1, "c"),
+        insert_at(&file, 1, "d").with_priority(-1),
+    ];
+    assert_eq!(apply(&file, patches), "xbacdy");
+}
+
+#[test]
+fn test_insertions_precede_replacement_at_same_start() {
+    let (_dir, file) = setup("0123");
+    let start = Boundary::new(Target::Char(1), BoundaryMode::Include);
+    let end = Boundary::new(Target::Char(3), BoundaryMode::Exclude);
+    let replace = Patch::new(file.clone(), Snippet::Between { start, end }, "R");
+    let patches = vec![replace, insert_at(&file, 1, "a"), insert_at(&file, 1, "b")];
+    assert_eq!(apply(&file, patches), "0abR3");
+}
+
+#[test]
+fn test_insertions_at_file_end() {
+    let (_dir, file) = setup("x\n");
+    let patches = vec![
+        Patch::new(file.clone(), Snippet::All, "one\n").with_operation(Operation::Append),
+        Patch::new(file.clone(), Snippet::All, "two\n").with_operation(Operation::Append),
+    ];
+    assert_eq!(apply(&file, patches), "x\none\ntwo\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_priority_json() {
+    let json = r#"[{"file": "a.rs", "replacement": "x", "priority": -2}, {"file": "b.rs"}]"#;
+    let set = PatchSet::from_json(json).unwrap();
+    assert_eq!(set.patches()[0].priority, -2);
+    assert_eq!(set.patches()[1].priority, 0);
+}
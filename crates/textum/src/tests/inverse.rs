@@ -0,0 +1,216 @@
+use super::*;
+use crate::patch::{Expected, Operation};
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::Target;
+use std::fs;
+use tempfile::TempDir;
+
+/// Applies `patches` to a file holding `original`, then the inverse, checking both results.
+fn assert_round_trip(before: &str, patches: Vec<Patch>, after: &str) -> PatchSet {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("file.txt").to_string_lossy().to_string();
+    fs::write(&file, before).unwrap();
+
+    let mut set = PatchSet::new();
+    for patch in patches {
+        set.add(Patch {
+            file: file.clone(),
+            ..patch
+        });
+    }
+
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    assert_eq!(results[&file], after);
+    fs::write(&file, &results[&file]).unwrap();
+
+    let restored = inverse.apply_to_files().unwrap();
+    assert_eq!(restored[&file], before);
+    inverse
+}
+
+fn literal(needle: &str, replacement: &str) -> Patch {
+    Patch::from_literal_target(String::new(), needle, BoundaryMode::Include, replacement)
+}
+
+#[test]
+fn test_inverse_of_replacements() {
+    let inverse = assert_round_trip(
+        "let alpha = beta;\n",
+        vec![literal("alpha", "a"), literal("beta", "b")],
+        "let a = b;\n",
+    );
+    assert_eq!(inverse.patches().len(), 2);
+}
+
+#[test]
+fn test_inverse_of_deletion_and_insertions() {
+    assert_round_trip(
+        "one\ntwo\nthree\n",
+        vec![
+            Patch::from_line_range(String::new(), 1, 2, ""),
+            literal("one", "zero\n").with_operation(Operation::InsertBefore),
+        ],
+        "zero\none\nthree\n",
+    );
+}
+
+#[test]
+fn test_inverse_restores_mixed_line_endings_verbatim() {
+    let inverse = assert_round_trip(
+        "x\r\ny\r\nz\n",
+        vec![Patch::from_line_range(String::new(), 2, 3, "")],
+        "x\r\ny\r\n",
+    );
+    assert_eq!(inverse.line_ending(), LineEnding::Verbatim);
+}
+
+#[test]
+fn test_inverse_keeps_match_placeholders_in_original() {
+    assert_round_trip(
+        "<p>{{match}}</p>\n\\{{match}}\nend\n",
+        vec![
+            literal("<p>{{match}}</p>", "<p>x</p>"),
+            Patch::from_line_range(String::new(), 1, 2, ""),
+        ],
+        "<p>x</p>\nend\n",
+    );
+}
+
+#[test]
+fn test_inverse_at_file_end() {
+    assert_round_trip(
+        "body",
+        vec![literal("", "\ntail").with_operation(Operation::Append)],
+        "body\ntail",
+    );
+    assert_round_trip("keep drop", vec![literal(" drop", "")], "keep");
+}
+
+#[test]
+fn test_inverse_of_whole_file_replacement() {
+    assert_round_trip(
+        "old contents\n",
+        vec![Patch::new(String::new(), Snippet::All, "new\n")],
+        "new\n",
+    );
+}
+
+#[test]
+fn test_inverse_of_insertion_into_empty_file() {
+    assert_round_trip(
+        "",
+        vec![literal("", "created\n").with_operation(Operation::Prepend)],
+        "created\n",
+    );
+}
+
+#[test]
+fn test_inverse_of_adjacent_edits() {
+    // Touching regions are merged into a single inverse patch
+    let inverse = assert_round_trip("abcdef", vec![literal("bc", ""), literal("de", "X")], "aXf");
+    assert_eq!(inverse.patches().len(), 1);
+}
+
+#[test]
+fn test_inverse_of_overlapping_deletions() {
+    let start = Boundary::new(Target::Char(1), BoundaryMode::Include);
+    let end = Boundary::new(Target::Char(4), BoundaryMode::Exclude);
+    let first = Patch::new(String::new(), Snippet::Between { start, end }, "");
+    let start = Boundary::new(Target::Char(3), BoundaryMode::Include);
+    let end = Boundary::new(Target::Char(6), BoundaryMode::Exclude);
+    let second = Patch::new(String::new(), Snippet::Between { start, end }, "");
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("file.txt").to_string_lossy().to_string();
+    fs::write(&file, "0123456789").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(Patch {
+        file: file.clone(),
+        ..first
+    });
+    set.add(Patch {
+        file: file.clone(),
+        ..second
+    });
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    fs::write(&file, &results[&file]).unwrap();
+
+    assert_eq!(inverse.apply_to_files().unwrap()[&file], "0123456789");
+}
+
+#[test]
+fn test_inverse_with_multibyte_text() {
+    assert_round_trip(
+        "naïve café 🎉\n",
+        vec![literal("ï", "i"), literal("🎉", "!")],
+        "naive café !\n",
+    );
+}
+
+#[test]
+fn test_inverse_skips_idempotent_noops() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("file.txt").to_string_lossy().to_string();
+    fs::write(&file, "# header\nbody\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(
+        Patch::new(file.clone(), Snippet::All, "# header\n")
+            .with_operation(Operation::Prepend)
+            .idempotent(),
+    );
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+
+    assert_eq!(results[&file], "# header\nbody\n");
+    assert!(inverse.patches().is_empty());
+}
+
+#[test]
+fn test_inverse_refuses_changed_file() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("file.txt").to_string_lossy().to_string();
+    fs::write(&file, "value = 1\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(Patch::from_literal_target(
+        file.clone(),
+        "1",
+        BoundaryMode::Include,
+        "2",
+    ));
+    let (_, inverse) = set.apply_to_files_with_inverse().unwrap();
+    assert_eq!(
+        inverse.patches()[0].expected,
+        Some(Expected::Text("2".to_string()))
+    );
+
+    // The patched file was edited again before the undo
+    fs::write(&file, "value = 3\n").unwrap();
+    assert!(matches!(
+        inverse.apply_to_files().as_ref().map_err(PatchError::root),
+        Err(PatchError::ExpectedMismatch { .. })
+    ));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_inverse_json_round_trip() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("file.txt").to_string_lossy().to_string();
+    fs::write(&file, "abc\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(Patch::from_literal_target(
+        file.clone(),
+        "b",
+        BoundaryMode::Include,
+        "B",
+    ));
+    let (results, inverse) = set.apply_to_files_with_inverse().unwrap();
+    fs::write(&file, &results[&file]).unwrap();
+
+    let reloaded = PatchSet::from_json(&inverse.to_json()).unwrap();
+    assert_eq!(reloaded.line_ending(), LineEnding::Verbatim);
+    assert_eq!(reloaded.apply_to_files().unwrap()[&file], "abc\n");
+}
@@ -0,0 +1,101 @@
+use super::*;
+use crate::composer::PatchSet;
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::Target;
+use std::fs;
+use tempfile::TempDir;
+
+fn apply_with(content: &str, patch: Patch, line_ending: LineEnding) -> String {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("a.txt").to_string_lossy().to_string();
+    fs::write(&file, content).unwrap();
+
+    let mut patch = patch;
+    patch.file.clone_from(&file);
+    let mut set = PatchSet::new().with_line_ending(line_ending);
+    set.add(patch);
+    set.apply_to_files().unwrap().remove(&file).unwrap()
+}
+
+fn literal(needle: &str, replacement: &str) -> Patch {
+    Patch::from_literal_target(String::new(), needle, BoundaryMode::Include, replacement)
+}
+
+#[test]
+fn test_detect_dominant() {
+    assert_eq!(detect(&Rope::from_str("a\r\nb\r\nc\n")), Some("\r\n"));
+    assert_eq!(detect(&Rope::from_str("a\nb\nc\r\n")), Some("\n"));
+    assert_eq!(detect(&Rope::from_str("a\rb")), None);
+    assert_eq!(detect(&Rope::from_str("")), None);
+}
+
+#[test]
+fn test_normalise_borrows_when_consistent() {
+    assert!(matches!(normalise("a\r\nb", "\r\n"), Cow::Borrowed(_)));
+    assert!(matches!(normalise("a\nb", "\n"), Cow::Borrowed(_)));
+    assert!(matches!(normalise("ab", "\r\n"), Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_normalise_mixed_text() {
+    assert_eq!(normalise("a\nb\r\nc\rd", "\r\n"), "a\r\nb\r\nc\rd");
+    assert_eq!(normalise("a\nb\r\nc\rd", "\n"), "a\nb\nc\rd");
+}
+
+#[test]
+fn test_preserve_crlf_file() {
+    let result = apply_with(
+        "fn a() {}\r\nfn b() {}\r\n",
+        literal("fn b() {}", "fn b() {\n    todo!()\n}"),
+        LineEnding::Preserve,
+    );
+    assert_eq!(result, "fn a() {}\r\nfn b() {\r\n    todo!()\r\n}\r\n");
+}
+
+#[test]
+fn test_preserve_lf_file() {
+    let result = apply_with("a\nb\n", literal("b", "b\r\nc"), LineEnding::Preserve);
+    assert_eq!(result, "a\nb\nc\n");
+}
+
+#[test]
+fn test_preserve_without_line_breaks_is_verbatim() {
+    let result = apply_with("a", literal("a", "a\r\nb\nc"), LineEnding::Preserve);
+    assert_eq!(result, "a\r\nb\nc");
+}
+
+#[test]
+fn test_force_crlf() {
+    let result = apply_with("a\nb\n", literal("b", "b\nc"), LineEnding::Crlf);
+    assert_eq!(result, "a\nb\r\nc\n");
+}
+
+#[test]
+fn test_wrap_prefix_and_suffix_normalised() {
+    let patch = literal("body", "").with_operation(Operation::Wrap {
+        prefix: "{\n".to_string(),
+        suffix: "\n}".to_string(),
+    });
+    let result = apply_with("x\r\nbody\r\n", patch, LineEnding::Preserve);
+    assert_eq!(result, "x\r\n{\r\nbody\r\n}\r\n");
+}
+
+#[test]
+fn test_line_targets_span_crlf_terminator() {
+    let start = Boundary::new(Target::Line(1), BoundaryMode::Include);
+    let end = Boundary::new(Target::Line(2), BoundaryMode::Exclude);
+    let patch = Patch::new(String::new(), Snippet::Between { start, end }, "B\n");
+    let result = apply_with("a\r\nb\r\nc\r\n", patch, LineEnding::Preserve);
+    assert_eq!(result, "a\r\nB\r\nc\r\n");
+}
+
+#[test]
+fn test_position_column_cannot_split_crlf() {
+    let rope = Rope::from_str("ab\r\ncd\r\n");
+    // Column 3 is the terminator itself, column 4 would fall between `\r` and `\n`
+    assert_eq!(
+        Target::Position { line: 1, col: 3 }.resolve(&rope).unwrap(),
+        2
+    );
+    assert!(Target::Position { line: 1, col: 4 }.resolve(&rope).is_err());
+}
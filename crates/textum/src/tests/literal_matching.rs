@@ -0,0 +1,259 @@
+use super::*;
+use crate::snip::snippet::{Boundary, BoundaryMode, Extent, Snippet};
+use crate::snip::target::error::TargetError;
+use crate::snip::Target;
+use ropey::Rope;
+
+fn word() -> LiteralOptions {
+    LiteralOptions {
+        word_boundary: true,
+        ..Default::default()
+    }
+}
+
+fn nocase() -> LiteralOptions {
+    LiteralOptions {
+        case_insensitive: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_default_options_match_like_literal() {
+    let rope = Rope::from_str("valid id");
+    assert_eq!(
+        find_literal(&rope, "id", &LiteralOptions::default(), 0),
+        Some((3, 5))
+    );
+}
+
+#[test]
+fn test_word_boundary_skips_inside_words() {
+    let rope = Rope::from_str("valid idx id");
+    assert_eq!(find_literal(&rope, "id", &word(), 0), Some((10, 12)));
+}
+
+#[test]
+fn test_word_boundary_accepts_punctuation_edges() {
+    let rope = Rope::from_str("f(id)");
+    assert_eq!(find_literal(&rope, "id", &word(), 0), Some((2, 4)));
+}
+
+#[test]
+fn test_word_boundary_at_rope_edges() {
+    let rope = Rope::from_str("id");
+    assert_eq!(find_literal(&rope, "id", &word(), 0), Some((0, 2)));
+}
+
+#[test]
+fn test_word_boundary_unicode_letters() {
+    // "é" is a word character, so "caf" inside "café" is not a whole word
+    let rope = Rope::from_str("café caf");
+    assert_eq!(find_literal(&rope, "caf", &word(), 0), Some((5, 8)));
+}
+
+#[test]
+fn test_word_boundary_combining_mark() {
+    // Decomposed "é": the combining acute accent continues the word
+    let rope = Rope::from_str("cafe\u{301} cafe");
+    assert_eq!(find_literal(&rope, "cafe", &word(), 0), Some((6, 10)));
+}
+
+#[test]
+fn test_word_boundary_underscore_is_word() {
+    let rope = Rope::from_str("my_id id");
+    assert_eq!(find_all_literal(&rope, "id", &word()), vec![(6, 8)]);
+}
+
+#[test]
+fn test_word_boundary_overlapping_candidates() {
+    // Neither overlapping candidate inside "aaa" is a whole word, but the standalone one is
+    let rope = Rope::from_str("aaa aa");
+    assert_eq!(find_all_literal(&rope, "aa", &word()), vec![(4, 6)]);
+}
+
+#[test]
+fn test_find_from_offset() {
+    let rope = Rope::from_str("id id id");
+    assert_eq!(find_literal(&rope, "id", &word(), 1), Some((3, 5)));
+}
+
+#[test]
+fn test_not_found() {
+    let rope = Rope::from_str("valid");
+    let target = Target::literal_with("id", word());
+    assert_eq!(target.resolve(&rope), Err(TargetError::NotFound));
+}
+
+#[test]
+fn test_snippet_replace_whole_word() {
+    let rope = Rope::from_str("let idx = id + valid;");
+    let boundary = Boundary::new(Target::literal_with("id", word()), BoundaryMode::Include);
+    let result = Snippet::At(boundary).replace(&rope, "key").unwrap();
+    assert_eq!(result.to_string(), "let idx = key + valid;");
+}
+
+#[test]
+fn test_matching_extent_uses_options() {
+    let rope = Rope::from_str("start idx id end");
+    let boundary = Boundary::new(
+        Target::Literal("start".to_string()),
+        BoundaryMode::Extend(Extent::Matching(1, Target::literal_with("id", word()))),
+    );
+    let resolution = boundary.resolve(&rope).unwrap();
+    assert_eq!((resolution.start, resolution.end), (5, 12));
+}
+
+#[test]
+fn test_case_insensitive_ascii() {
+    let rope = Rope::from_str("See the README for details");
+    assert_eq!(find_literal(&rope, "readme", &nocase(), 0), Some((8, 14)));
+}
+
+#[test]
+fn test_case_insensitive_returns_original_range() {
+    let rope = Rope::from_str("ÀB àb");
+    assert_eq!(
+        find_all_literal(&rope, "àB", &nocase()),
+        vec![(0, 2), (3, 5)]
+    );
+}
+
+#[test]
+fn test_case_insensitive_greek_sigma() {
+    // Final sigma, small sigma and capital sigma all fold to the same letter
+    let rope = Rope::from_str("ΟΔΟΣ οδος");
+    assert_eq!(
+        find_all_literal(&rope, "οδος", &nocase()),
+        vec![(0, 4), (5, 9)]
+    );
+    let rope = Rope::from_str("οδοσ");
+    assert_eq!(find_literal(&rope, "ΟΔΟΣ", &nocase(), 0), Some((0, 4)));
+}
+
+#[test]
+fn test_case_insensitive_kelvin_and_long_s() {
+    let rope = Rope::from_str("\u{212A}elvin ſtar");
+    assert_eq!(find_literal(&rope, "kelvin", &nocase(), 0), Some((0, 6)));
+    assert_eq!(find_literal(&rope, "STAR", &nocase(), 0), Some((7, 11)));
+}
+
+#[test]
+fn test_case_insensitive_no_multichar_folding() {
+    // Simple folding does not expand "ß" to "ss"
+    let rope = Rope::from_str("straße");
+    assert_eq!(find_literal(&rope, "STRASSE", &nocase(), 0), None);
+    assert_eq!(find_literal(&rope, "STRAẞE", &nocase(), 0), Some((0, 6)));
+}
+
+#[test]
+#[allow(clippy::needless_update)]
+fn test_case_insensitive_with_word_boundary() {
+    let options = LiteralOptions {
+        word_boundary: true,
+        case_insensitive: true,
+        ..Default::default()
+    };
+    let rope = Rope::from_str("Identity ID id");
+    assert_eq!(
+        find_all_literal(&rope, "id", &options),
+        vec![(9, 11), (12, 14)]
+    );
+}
+
+#[test]
+fn test_case_insensitive_multibyte_offsets() {
+    let rope = Rope::from_str("日本 HELLO");
+    let target = Target::literal_with("hello", nocase());
+    assert_eq!(target.resolve_range(&rope).unwrap(), (3, 8));
+}
+
+#[cfg(feature = "normalization")]
+mod normalization {
+    use super::*;
+    use crate::snip::target::literal::Normalization;
+
+    fn normalized(form: Normalization) -> LiteralOptions {
+        LiteralOptions {
+            normalization: Some(form),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_nfc_needle_matches_nfd_text() {
+        let rope = Rope::from_str("un cafe\u{301} noir");
+        assert_eq!(
+            find_literal(&rope, "café", &normalized(Normalization::Nfc), 0),
+            Some((3, 8))
+        );
+    }
+
+    #[test]
+    fn test_nfd_needle_matches_nfc_text() {
+        let rope = Rope::from_str("un café noir");
+        assert_eq!(
+            find_literal(&rope, "cafe\u{301}", &normalized(Normalization::Nfc), 0),
+            Some((3, 7))
+        );
+    }
+
+    #[test]
+    fn test_nfc_does_not_split_combining_marks() {
+        // "e" alone must not match the base of a decomposed "é"
+        let rope = Rope::from_str("e\u{301} e");
+        assert_eq!(
+            find_all_literal(&rope, "e", &normalized(Normalization::Nfc)),
+            vec![(3, 4)]
+        );
+    }
+
+    #[test]
+    fn test_nfc_hangul_jamo() {
+        // Conjoining jamo compose into a single precomposed syllable
+        let rope = Rope::from_str("x\u{1112}\u{1161}\u{11AB}y");
+        assert_eq!(
+            find_literal(&rope, "한", &normalized(Normalization::Nfc), 0),
+            Some((1, 4))
+        );
+    }
+
+    #[test]
+    fn test_nfkc_matches_compatibility_forms() {
+        let rope = Rope::from_str("the ﬁle ２");
+        let options = normalized(Normalization::Nfkc);
+        assert_eq!(find_literal(&rope, "file", &options, 0), Some((4, 7)));
+        assert_eq!(find_literal(&rope, "2", &options, 0), Some((8, 9)));
+        // Half of an expanded ligature is not a match
+        assert_eq!(find_literal(&rope, "f", &options, 0), None);
+    }
+
+    #[test]
+    fn test_nfc_leaves_compatibility_forms_distinct() {
+        let rope = Rope::from_str("ﬁle");
+        assert_eq!(
+            find_literal(&rope, "file", &normalized(Normalization::Nfc), 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_normalization_with_case_folding() {
+        let options = LiteralOptions {
+            case_insensitive: true,
+            normalization: Some(Normalization::Nfc),
+            ..Default::default()
+        };
+        let rope = Rope::from_str("CAFE\u{301}");
+        assert_eq!(find_literal(&rope, "café", &options, 0), Some((0, 5)));
+    }
+
+    #[test]
+    fn test_patch_replaces_original_range() {
+        let rope = Rope::from_str("un cafe\u{301} noir");
+        let target = Target::literal_with("café", normalized(Normalization::Nfc));
+        let boundary = Boundary::new(target, BoundaryMode::Include);
+        let result = Snippet::At(boundary).replace(&rope, "thé").unwrap();
+        assert_eq!(result.to_string(), "un thé noir");
+    }
+}
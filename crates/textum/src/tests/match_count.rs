@@ -0,0 +1,117 @@
+use super::*;
+use crate::snip::snippet::{Boundary, BoundaryMode, MatchCount, Snippet};
+use crate::snip::Target;
+use std::fs;
+use tempfile::TempDir;
+
+fn counted_patch(file: &str, needle: &str, count: MatchCount, replacement: &str) -> Patch {
+    let boundary = Boundary::new(Target::Literal(needle.to_string()), BoundaryMode::Include)
+        .with_expected_count(count);
+    Patch::new(file.to_string(), Snippet::At(boundary), replacement)
+}
+
+#[test]
+fn test_exact_count_satisfied() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("count.txt");
+    fs::write(&file, "let x = 1;\nlet y = 2;\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(counted_patch(&file, "x", MatchCount::Exactly(1), "z"));
+
+    let results = set.apply_to_files().unwrap();
+    assert_eq!(results.get(&file).unwrap(), "let z = 1;\nlet y = 2;\n");
+}
+
+#[test]
+fn test_exact_count_mismatch_reports_positions() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("count.txt");
+    fs::write(&file, "let\nlet\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(counted_patch(&file, "let", MatchCount::Exactly(1), "const"));
+
+    match set.apply_to_files().as_ref().map_err(PatchError::root) {
+        Err(PatchError::MatchCountMismatch {
+            actual, positions, ..
+        }) => {
+            assert_eq!(*actual, 2);
+            assert_eq!(*positions, vec![(0, 3), (4, 7)]);
+        }
+        other => panic!("expected MatchCountMismatch, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_at_least_once_fails_when_absent() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("count.txt");
+    fs::write(&file, "hello").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(counted_patch(
+        &file,
+        "world",
+        MatchCount::AtLeast(1),
+        "rust",
+    ));
+
+    assert!(matches!(
+        set.apply_to_files().as_ref().map_err(PatchError::root),
+        Err(PatchError::MatchCountMismatch { actual: 0, .. })
+    ));
+}
+
+#[test]
+fn test_count_mismatch_aborts_set_without_writing() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("count.txt");
+    fs::write(&file, "a a a").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(counted_patch(
+        &file,
+        "a",
+        MatchCount::Range { min: 1, max: 2 },
+        "b",
+    ));
+
+    assert!(set.apply_to_files().is_err());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "a a a");
+}
+
+#[test]
+fn test_count_ignored_when_snippet_is_not_resolved() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("count.txt");
+    fs::write(&file, "body\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut set = PatchSet::new();
+    set.add(
+        counted_patch(&file, "absent", MatchCount::Exactly(1), "head\n")
+            .with_operation(crate::patch::Operation::Prepend),
+    );
+    set.add(
+        counted_patch(&file, "absent", MatchCount::Exactly(1), "tail\n")
+            .with_operation(crate::patch::Operation::Append),
+    );
+
+    let results = set.apply_to_files().unwrap();
+    assert_eq!(results.get(&file).unwrap(), "head\nbody\ntail\n");
+}
+
+#[test]
+fn test_match_count_admits() {
+    assert!(MatchCount::Exactly(0).admits(0));
+    assert!(!MatchCount::Exactly(2).admits(3));
+    assert!(MatchCount::AtLeast(2).admits(5));
+    assert!(MatchCount::AtMost(2).admits(0));
+    assert!(!MatchCount::AtMost(2).admits(3));
+    assert!(!MatchCount::Range { min: 1, max: 2 }.admits(0));
+}
@@ -0,0 +1,268 @@
+use super::*;
+use crate::composer::test_helpers::setup;
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::target::Target;
+use std::fs;
+
+fn chars(file: &str, start: usize, end: usize, replacement: &str) -> Patch {
+    let start = Boundary::new(Target::Char(start), BoundaryMode::Include);
+    let end = Boundary::new(Target::Char(end), BoundaryMode::Exclude);
+    Patch::new(
+        file.to_string(),
+        Snippet::Between { start, end },
+        replacement,
+    )
+}
+
+fn set_of(policy: OverlapPolicy, patches: Vec<Patch>) -> PatchSet {
+    let mut set = PatchSet::new().with_overlap_policy(policy);
+    for patch in patches {
+        set.add(patch);
+    }
+    set
+}
+
+#[test]
+fn test_error_names_both_patches() {
+    let (_dir, file) = setup("0123456789");
+    let err = set_of(
+        OverlapPolicy::Error,
+        vec![
+            chars(&file, 1, 4, "a").with_id("first"),
+            chars(&file, 3, 6, "b").with_id("second"),
+        ],
+    )
+    .apply_to_files()
+    .unwrap_err();
+
+    match &err {
+        PatchError::OverlappingRanges {
+            file: f,
+            range1,
+            range2,
+            patches,
+        } => {
+            assert_eq!(*f, file);
+            assert_eq!((*range1, *range2), ((1, 4), (3, 6)));
+            assert_eq!(patches[0].id.as_deref(), Some("first"));
+            assert_eq!(patches[1].index, 1);
+        }
+        other => panic!("expected OverlappingRanges, got {other:?}"),
+    }
+    assert!(err.to_string().contains(r#"patch 0 ("first")"#));
+    assert!(err.to_string().contains(r#"patch 1 ("second")"#));
+}
+
+#[test]
+fn test_merge_applies_identical_edits_once() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![chars(&file, 1, 4, "x"), chars(&file, 1, 4, "x")];
+    assert!(set_of(OverlapPolicy::Error, patches.clone())
+        .apply_to_files()
+        .is_err());
+    assert_eq!(
+        set_of(OverlapPolicy::Merge, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "0x456789"
+    );
+}
+
+#[test]
+fn test_merge_rejects_different_edits() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![chars(&file, 1, 4, "x"), chars(&file, 1, 4, "y")];
+    assert!(matches!(
+        set_of(OverlapPolicy::Merge, patches).apply_to_files(),
+        Err(PatchError::OverlappingRanges { .. })
+    ));
+}
+
+#[test]
+fn test_first_and_last_wins() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 1, 4, "a"),
+        chars(&file, 3, 6, "b"),
+        chars(&file, 8, 9, "c"),
+    ];
+    assert_eq!(
+        set_of(OverlapPolicy::FirstWins, patches.clone())
+            .apply_to_files()
+            .unwrap()[&file],
+        "0a4567c9"
+    );
+    assert_eq!(
+        set_of(OverlapPolicy::LastWins, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "012b67c9"
+    );
+}
+
+#[test]
+fn test_overlapping_deletions_are_combined() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![chars(&file, 1, 4, ""), chars(&file, 3, 6, "")];
+    assert_eq!(
+        set_of(OverlapPolicy::Error, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "06789"
+    );
+}
+
+#[test]
+fn test_combined_deletions_keep_their_own_order() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 1, 4, ""),
+        chars(&file, 5, 7, "x"),
+        chars(&file, 3, 6, ""),
+    ];
+    // The replacement only overlaps the deletion declared after it
+    assert_eq!(
+        set_of(OverlapPolicy::FirstWins, patches.clone())
+            .apply_to_files()
+            .unwrap()[&file],
+        "04x789"
+    );
+    assert_eq!(
+        set_of(OverlapPolicy::LastWins, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "06789"
+    );
+}
+
+#[test]
+fn test_merge_joins_adjacent_edits() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 1, 4, "x"),
+        chars(&file, 4, 6, "y"),
+        chars(&file, 6, 6, "!"),
+        chars(&file, 6, 6, "?").with_priority(1),
+        chars(&file, 8, 9, "z"),
+    ];
+    let outcome = set_of(OverlapPolicy::Merge, patches).apply().unwrap();
+    assert_eq!(
+        outcome.changes[&file],
+        FileChange::Write("0xy?!67z9".to_string())
+    );
+    assert!(outcome
+        .report
+        .patches
+        .iter()
+        .all(|p| p.status == PatchStatus::Applied));
+
+    // The joined edit is undone as one
+    fs::write(&file, "0xy?!67z9").unwrap();
+    assert_eq!(
+        outcome.inverse.apply_to_files().unwrap()[&file],
+        "0123456789"
+    );
+}
+
+#[test]
+fn test_deletion_overlapping_replacement_fails() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![chars(&file, 1, 4, ""), chars(&file, 3, 6, "x")];
+    assert!(set_of(OverlapPolicy::Error, patches)
+        .apply_to_files()
+        .is_err());
+}
+
+#[test]
+fn test_insertion_inside_replacement_fails() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![chars(&file, 1, 4, "x"), chars(&file, 2, 2, "y")];
+    assert!(set_of(OverlapPolicy::Error, patches)
+        .apply_to_files()
+        .is_err());
+}
+
+#[test]
+fn test_insertion_touching_replacement() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 1, 4, "x"),
+        chars(&file, 1, 1, "<"),
+        chars(&file, 4, 4, ">"),
+    ];
+    assert_eq!(
+        set_of(OverlapPolicy::Error, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "0<x>456789"
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_overlap_policy_json() {
+    let set = PatchSet::from_json(r#"{"overlap_policy": "LastWins", "patches": []}"#).unwrap();
+    assert_eq!(set.overlap_policy(), OverlapPolicy::LastWins);
+    assert_eq!(
+        PatchSet::from_json(&set.to_json())
+            .unwrap()
+            .overlap_policy(),
+        OverlapPolicy::LastWins
+    );
+}
+
+#[test]
+fn test_first_wins_keeps_touching_edits() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 2, 2, "<"),
+        chars(&file, 2, 5, "x"),
+        chars(&file, 5, 5, ">"),
+        chars(&file, 3, 3, "!"),
+        chars(&file, 0, 9, "all"),
+    ];
+    assert_eq!(
+        set_of(OverlapPolicy::FirstWins, patches.clone())
+            .apply_to_files()
+            .unwrap()[&file],
+        "01<x>56789"
+    );
+    assert_eq!(
+        set_of(OverlapPolicy::LastWins, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "all9"
+    );
+}
+
+#[test]
+fn test_nested_ranges_are_detected() {
+    let (_dir, file) = setup("0123456789");
+    let patches = vec![
+        chars(&file, 0, 9, "a"),
+        chars(&file, 4, 5, "b"),
+        chars(&file, 7, 8, "c"),
+    ];
+    match set_of(OverlapPolicy::Error, patches).apply_to_files() {
+        Err(PatchError::OverlappingRanges { range1, .. }) => assert_eq!(range1, (0, 9)),
+        other => panic!("expected OverlappingRanges, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_edits_are_not_resolved_again_after_earlier_edits() {
+    let (_dir, file) = setup("aab");
+    let pattern = Boundary::new(Target::pattern("a+").unwrap(), BoundaryMode::Include);
+    let patches = vec![
+        Patch::new(file.clone(), Snippet::At(pattern), "Z"),
+        chars(&file, 2, 2, "a"),
+    ];
+    // Resolving "a+" again after the insertion would also replace the inserted "a"
+    assert_eq!(
+        set_of(OverlapPolicy::Error, patches)
+            .apply_to_files()
+            .unwrap()[&file],
+        "Zab"
+    );
+}
@@ -0,0 +1,168 @@
+use super::*;
+use crate::composer::PatchSet;
+use std::fs;
+use tempfile::TempDir;
+
+fn literal_patch(needle: &str, replacement: &str, operation: Operation) -> Patch {
+    Patch::from_literal_target(
+        "test.txt".to_string(),
+        needle,
+        BoundaryMode::Include,
+        replacement,
+    )
+    .with_operation(operation)
+}
+
+fn apply_to(content: &str, patch: &Patch) -> String {
+    let mut rope = Rope::from_str(content);
+    patch.apply(&mut rope).unwrap();
+    rope.to_string()
+}
+
+#[test]
+fn test_replace_is_default() {
+    let patch = Patch::new("test.txt".to_string(), Snippet::All, "new");
+    assert_eq!(patch.operation, Operation::Replace);
+    assert_eq!(apply_to("old", &patch), "new");
+}
+
+#[test]
+fn test_insert_before() {
+    let patch = literal_patch("world", "big ", Operation::InsertBefore);
+    assert_eq!(apply_to("hello world", &patch), "hello big world");
+}
+
+#[test]
+fn test_insert_after() {
+    let patch = literal_patch("hello", ",", Operation::InsertAfter);
+    assert_eq!(apply_to("hello world", &patch), "hello, world");
+}
+
+#[test]
+fn test_delete_ignores_replacement() {
+    let patch = literal_patch(" world", "ignored", Operation::Delete);
+    assert_eq!(apply_to("hello world", &patch), "hello");
+}
+
+#[test]
+fn test_wrap() {
+    let patch = literal_patch(
+        "world",
+        "",
+        Operation::Wrap {
+            prefix: "<b>".to_string(),
+            suffix: "</b>".to_string(),
+        },
+    );
+    assert_eq!(apply_to("hello world", &patch), "hello <b>world</b>");
+}
+
+#[test]
+fn test_prepend_and_append_ignore_snippet() {
+    // The snippet would not resolve, proving file-level operations never consult it
+    let prepend = literal_patch("absent", "# header\n", Operation::Prepend);
+    let append = literal_patch("absent", "# footer\n", Operation::Append);
+
+    assert_eq!(apply_to("body\n", &prepend), "# header\nbody\n");
+    assert_eq!(apply_to("body\n", &append), "body\n# footer\n");
+}
+
+#[test]
+fn test_resolve_produces_edit() {
+    let rope = Rope::from_str("abc");
+    let patch = literal_patch("b", "X", Operation::InsertAfter);
+    assert_eq!(patch.resolve(&rope).unwrap(), Some(Edit::new(2, 2, "X")));
+}
+
+#[test]
+fn test_insertions_around_replaced_range_compose() {
+    // Zero-width insertions adjacent to a replaced range do not overlap it
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("ops.txt");
+    fs::write(&file, "let x = 1;\n").unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let patch = |needle: &str, replacement: &str, operation| {
+        Patch::from_literal_target(file.clone(), needle, BoundaryMode::Include, replacement)
+            .with_operation(operation)
+    };
+
+    // The prepend and the insertion before "let" share position 0, so keep declaration order
+    let mut set = PatchSet::new();
+    set.add(patch("", "// generated\n", Operation::Prepend));
+    set.add(patch("let", "pub ", Operation::InsertBefore));
+    set.add(patch("x", "y", Operation::Replace));
+    set.add(patch(";", " // changed", Operation::InsertAfter));
+
+    let results = set.apply_to_files().unwrap();
+    assert_eq!(
+        results.get(&file).unwrap(),
+        "// generated\npub let y = 1; // changed\n"
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_operation_json_roundtrip() {
+    let patches = vec![
+        literal_patch("a", "", Operation::Delete),
+        literal_patch(
+            "b",
+            "",
+            Operation::Wrap {
+                prefix: "(".to_string(),
+                suffix: ")".to_string(),
+            },
+        ),
+    ];
+    let json = facet_json::to_string(&patches);
+    let parsed: Vec<Patch> = facet_json::from_str(&json).unwrap();
+
+    assert_eq!(parsed[0].operation, Operation::Delete);
+    assert_eq!(parsed[1].operation, patches[1].operation);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_operation_defaults_when_omitted() {
+    let json = r#"[{"file": "a.txt", "snippet": "All", "replacement": "x"}]"#;
+    let parsed: Vec<Patch> = facet_json::from_str(json).unwrap();
+    assert_eq!(parsed[0].operation, Operation::Replace);
+}
+
+#[test]
+fn test_template_in_insert_after_duplicates_anchor() {
+    let patch = Patch::from_line_range("test.txt".to_string(), 0, 1, "{{match}}")
+        .with_operation(Operation::InsertAfter);
+    assert_eq!(apply_to("row\nend\n", &patch), "row\nrow\nend\n");
+}
+
+#[test]
+fn test_template_in_append_refers_to_file() {
+    let patch = Patch::new("test.txt".to_string(), Snippet::All, "{{match}}")
+        .with_operation(Operation::Append);
+    assert_eq!(apply_to("ab", &patch), "abab");
+}
+
+#[test]
+fn test_template_with_idempotent_replace() {
+    let patch = literal_patch("value", "Some({{match}})", Operation::Replace).idempotent();
+    let mut rope = Rope::from_str("let x = value;");
+    assert_eq!(patch.apply(&mut rope).unwrap(), PatchStatus::Applied);
+    // The target now matches inside its own expansion, which is recognised as applied
+    assert_eq!(patch.apply(&mut rope).unwrap(), PatchStatus::Skipped);
+    assert_eq!(rope.to_string(), "let x = Some(value);");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_template_escaping_in_json() {
+    // In JSON a literal placeholder is written with an escaped backslash
+    let json = r#"[{
+        "file": "test.txt",
+        "snippet": {"At": {"target": {"Literal": "x"}, "mode": "Include"}},
+        "replacement": "\\{{match}} = {{match}}"
+    }]"#;
+    let parsed: Vec<Patch> = facet_json::from_str(json).unwrap();
+    assert_eq!(apply_to("x", &parsed[0]), "{{match}} = x");
+}
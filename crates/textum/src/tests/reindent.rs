@@ -0,0 +1,152 @@
+use super::*;
+use crate::patch::{Operation, Patch};
+use crate::snip::snippet::BoundaryMode;
+
+fn apply(content: &str, needle: &str, replacement: &str, operation: Operation) -> String {
+    let mut rope = Rope::from_str(content);
+    Patch::from_literal_target(
+        "a.py".to_string(),
+        needle,
+        BoundaryMode::Include,
+        replacement,
+    )
+    .with_operation(operation)
+    .reindent()
+    .apply(&mut rope)
+    .unwrap();
+    rope.to_string()
+}
+
+#[test]
+fn test_line_indent() {
+    let rope = Rope::from_str("a\n\t  b\n");
+    assert_eq!(line_indent(&rope, 0), "");
+    assert_eq!(line_indent(&rope, 5), "\t  ");
+}
+
+#[test]
+fn test_replace_nested_block_keeps_relative_indent() {
+    let content = "class A:\n    def f(self):\n        pass\n";
+    let replacement = "for x in xs:\n    if x:\n        yield x";
+    assert_eq!(
+        apply(content, "pass", replacement, Operation::Replace),
+        "class A:\n    def f(self):\n        for x in xs:\n            if x:\n                yield x\n"
+    );
+}
+
+#[test]
+fn test_common_indent_is_stripped() {
+    let content = "def f():\n    pass\n";
+    let replacement = "        a = 1\n        if a:\n            b()";
+    assert_eq!(
+        apply(content, "pass", replacement, Operation::Replace),
+        "def f():\n    a = 1\n    if a:\n        b()\n"
+    );
+}
+
+#[test]
+fn test_spaces_converted_to_tabs() {
+    let content = "fn f() {\n\tlet a = 1;\n}\n";
+    let replacement = "if a {\n  b();\n}";
+    assert_eq!(
+        apply(content, "let a = 1;", replacement, Operation::Replace),
+        "fn f() {\n\tif a {\n\t\tb();\n\t}\n}\n"
+    );
+}
+
+#[test]
+fn test_tabs_converted_to_spaces() {
+    let content = "def f():\n    pass\n";
+    let replacement = "if x:\n\treturn 1";
+    assert_eq!(
+        apply(content, "pass", replacement, Operation::Replace),
+        "def f():\n    if x:\n        return 1\n"
+    );
+}
+
+#[test]
+fn test_insert_after_whole_line() {
+    let content = "def f():\n    a()\n    b()\n";
+    assert_eq!(
+        apply(
+            content,
+            "    a()\n",
+            "log()\nif x:\n    y()\n",
+            Operation::InsertAfter
+        ),
+        "def f():\n    a()\n    log()\n    if x:\n        y()\n    b()\n"
+    );
+}
+
+#[test]
+fn test_insert_mid_line_adds_no_first_line_prefix() {
+    let content = "def f():\n    x = g(\n        1)\n";
+    assert_eq!(
+        apply(content, "g(", "\n2,", Operation::InsertAfter),
+        "def f():\n    x = g(\n    2,\n        1)\n"
+    );
+}
+
+#[test]
+fn test_blank_lines_are_left_empty() {
+    let content = "def f():\n    pass\n";
+    assert_eq!(
+        apply(content, "pass", "a()\n\n  \nb()", Operation::Replace),
+        "def f():\n    a()\n\n\n    b()\n"
+    );
+}
+
+#[test]
+fn test_unindented_target_only_dedents() {
+    assert_eq!(
+        apply("pass\n", "pass", "    a()\n      b()", Operation::Replace),
+        "a()\n  b()\n"
+    );
+}
+
+#[test]
+fn test_without_reindent_text_is_inserted_verbatim() {
+    let mut rope = Rope::from_str("def f():\n    pass\n");
+    Patch::from_literal_target(
+        "a.py".to_string(),
+        "pass",
+        BoundaryMode::Include,
+        "a()\nb()",
+    )
+    .apply(&mut rope)
+    .unwrap();
+    assert_eq!(rope.to_string(), "def f():\n    a()\nb()\n");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_reindent_json() {
+    let json = r#"[{
+        "file": "a.py",
+        "snippet": "All",
+        "replacement": "x",
+        "reindent": true
+    }]"#;
+    let parsed: Vec<Patch> = facet_json::from_str(json).unwrap();
+    assert!(parsed[0].reindent);
+}
+
+#[test]
+fn test_insert_before_indented_line_keeps_its_column() {
+    let content = "def f():\n    return 1\n";
+    assert_eq!(
+        apply(content, "return 1", "x = 1\n", Operation::InsertBefore),
+        "def f():\n    x = 1\n    return 1\n"
+    );
+}
+
+#[test]
+fn test_replace_ending_mid_line_leaves_no_blank_indent() {
+    let content = "def f():\n    pass\n    return\n";
+    let patched = apply(content, "pass", "x = 1\ny = 2\n", Operation::Replace);
+    assert!(patched.starts_with("def f():\n    x = 1\n    y = 2\n"));
+    assert!(patched.ends_with("\n    return\n"));
+    assert!(!patched
+        .lines()
+        .any(|line| !line.is_empty() && line.trim().is_empty()));
+}
@@ -239,3 +239,63 @@ fn test_replace_regex_pattern() {
 
     assert_eq!(result.to_string(), "version 2.0.0 is old");
 }
+
+#[test]
+fn test_replace_template_with_literal_target() {
+    // Tests that {{match}} expands to the selected text
+    let rope = Rope::from_str("let x = value;");
+    let target = Target::Literal("value".to_string());
+    let snippet = Snippet::At(Boundary::new(target, BoundaryMode::Include));
+
+    let result = snippet.replace(&rope, "Some({{match}})").unwrap();
+    assert_eq!(result.to_string(), "let x = Some(value);");
+}
+
+#[test]
+fn test_replace_template_duplicates_line() {
+    // Tests that a line target can be duplicated via its own text
+    let rope = Rope::from_str("a\nb\nc\n");
+    let snippet = Snippet::At(Boundary::new(Target::Line(1), BoundaryMode::Include));
+
+    let result = snippet.replace(&rope, "{{match}}{{match}}").unwrap();
+    assert_eq!(result.to_string(), "a\nb\nb\nc\n");
+}
+
+#[test]
+fn test_replace_template_with_between_and_char_targets() {
+    // Tests that the placeholder refers to the resolved range, not to a boundary target
+    let rope = Rope::from_str("[abc]");
+    let start = Boundary::new(Target::Char(0), BoundaryMode::Exclude);
+    let end = Boundary::new(Target::Char(4), BoundaryMode::Exclude);
+    let snippet = Snippet::Between { start, end };
+
+    let result = snippet.replace(&rope, "<{{match}}>").unwrap();
+    assert_eq!(result.to_string(), "[<abc>]");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_replace_template_with_pattern_target() {
+    let rope = Rope::from_str("total = 42;");
+    let target = Target::Pattern(r"\d+".to_string());
+    let snippet = Snippet::At(Boundary::new(target, BoundaryMode::Include));
+
+    let result = snippet.replace(&rope, "({{match}} * 2)").unwrap();
+    assert_eq!(result.to_string(), "total = (42 * 2);");
+}
+
+#[test]
+fn test_replace_template_escapes() {
+    // Tests that escaped placeholders stay literal and other backslashes are untouched
+    let rope = Rope::from_str("x");
+    let snippet = Snippet::At(Boundary::new(
+        Target::Literal("x".to_string()),
+        BoundaryMode::Include,
+    ));
+
+    let result = snippet.replace(&rope, r"\{{match}} is {{match}}").unwrap();
+    assert_eq!(result.to_string(), "{{match}} is x");
+
+    let result = snippet.replace(&rope, r"a\b \\{{match}}").unwrap();
+    assert_eq!(result.to_string(), r"a\b \x");
+}
@@ -0,0 +1,133 @@
+use crate::snip::snippet::{Boundary, BoundaryMode, Snippet};
+use crate::snip::span::{Location, Span};
+use crate::snip::Target;
+use ropey::Rope;
+
+#[test]
+fn test_location_ascii() {
+    // Tests that all coordinate systems agree on plain ASCII text
+    let rope = Rope::from_str("abc\ndef\n");
+    let loc = Location::new(&rope, 5);
+
+    assert_eq!(loc.char_idx, 5);
+    assert_eq!(loc.byte_idx, 5);
+    assert_eq!(loc.utf16_idx, 5);
+    assert_eq!((loc.line, loc.col), (1, 1));
+    assert_eq!((loc.line_one_indexed(), loc.col_one_indexed()), (2, 2));
+}
+
+#[test]
+fn test_location_multibyte() {
+    // Tests that byte and UTF-16 offsets diverge from char indices after multi-byte characters
+    // "é" is 2 UTF-8 bytes and 1 UTF-16 unit; "🎉" is 4 UTF-8 bytes and 2 UTF-16 units
+    let rope = Rope::from_str("é🎉x");
+    let loc = Location::new(&rope, 2);
+
+    assert_eq!(loc.char_idx, 2);
+    assert_eq!(loc.byte_idx, 6);
+    assert_eq!(loc.utf16_idx, 3);
+    assert_eq!((loc.line, loc.col), (0, 2));
+}
+
+#[test]
+fn test_location_at_end_of_rope() {
+    // Tests that the one-past-the-end position is describable (needed for exclusive ends)
+    let rope = Rope::from_str("ab\n");
+    let loc = Location::new(&rope, 3);
+
+    assert_eq!(loc.byte_idx, 3);
+    assert_eq!((loc.line, loc.col), (1, 0));
+}
+
+#[test]
+fn test_location_crlf() {
+    // Tests that CRLF counts as a single line break for line/column purposes
+    let rope = Rope::from_str("ab\r\ncd");
+    let loc = Location::new(&rope, 5);
+
+    assert_eq!((loc.line, loc.col), (1, 1));
+    assert_eq!(loc.byte_idx, 5);
+}
+
+#[test]
+fn test_span_text_and_length() {
+    // Tests that a span carries the selected text and reports its width
+    let rope = Rope::from_str("héllo wörld");
+    let span = Span::new(&rope, 6, 11);
+
+    assert_eq!(span.text, "wörld");
+    assert_eq!(span.len_chars(), 5);
+    assert!(!span.is_empty());
+    assert_eq!(span.start.byte_idx, 7);
+    assert_eq!(span.end.byte_idx, 13);
+}
+
+#[test]
+fn test_span_empty() {
+    // Tests that zero-width spans (insertion points) have empty text
+    let rope = Rope::from_str("hello");
+    let span = Span::new(&rope, 2, 2);
+
+    assert!(span.is_empty());
+    assert_eq!(span.text, "");
+}
+
+#[test]
+fn test_target_resolve_span_spanning_lines() {
+    // Tests a literal match crossing a line break reports distinct start and end lines
+    let rope = Rope::from_str("one\ntwo\nthree\n");
+    let span = Target::Literal("o\nth".to_string())
+        .resolve_span(&rope)
+        .unwrap();
+
+    assert_eq!((span.start.line, span.start.col), (1, 2));
+    assert_eq!((span.end.line, span.end.col), (2, 2));
+    assert_eq!(span.text, "o\nth");
+}
+
+#[test]
+fn test_target_resolve_span_not_found() {
+    // Tests that resolution errors propagate from resolve_span
+    let rope = Rope::from_str("hello");
+    assert!(Target::Literal("absent".to_string())
+        .resolve_span(&rope)
+        .is_err());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_pattern_span_after_multibyte_text() {
+    // Tests that regex match offsets are converted from bytes to characters
+    let rope = Rope::from_str("naïve 42");
+    let span = Target::Pattern(r"\d+".to_string())
+        .resolve_span(&rope)
+        .unwrap();
+
+    assert_eq!(span.text, "42");
+    assert_eq!(span.start.char_idx, 6);
+    assert_eq!(span.start.byte_idx, 7);
+}
+
+#[test]
+fn test_boundary_resolution_span() {
+    // Tests that a boundary resolution describes its range
+    let rope = Rope::from_str("fn main() {}\n");
+    let boundary = Boundary::new(Target::Literal("main".to_string()), BoundaryMode::Include);
+    let span = boundary.resolve(&rope).unwrap().span(&rope);
+
+    assert_eq!(span.text, "main");
+    assert_eq!(span.start.col_one_indexed(), 4);
+}
+
+#[test]
+fn test_snippet_resolution_span_utf16() {
+    // Tests that snippet spans report UTF-16 offsets suitable for LSP clients
+    let rope = Rope::from_str("let s = \"🦀\";\nlet t = 1;\n");
+    let snippet = Snippet::At(Boundary::new(Target::Line(1), BoundaryMode::Include));
+    let span = snippet.resolve(&rope).unwrap().span(&rope);
+
+    assert_eq!(span.text, "let t = 1;\n");
+    assert_eq!(span.start.char_idx, 13);
+    assert_eq!(span.start.utf16_idx, 14);
+    assert_eq!(span.start.byte_idx, 16);
+}
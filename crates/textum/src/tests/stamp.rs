@@ -0,0 +1,91 @@
+use super::*;
+use crate::composer::test_helpers::{literal, path};
+use crate::snip::snippet::Snippet;
+use crate::{Operation, Patch, PatchError, PatchSet};
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[test]
+fn test_stamp_records_length_and_hash() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "hello\n").unwrap();
+
+    let (content, stamp) = FileStamp::read(&file).unwrap().unwrap();
+    assert_eq!(content, "hello\n");
+    assert_eq!(stamp.len, 6);
+    assert_eq!(stamp.hash, content_hash("hello\n"));
+    assert!(FileStamp::read(&path(&dir, "missing.txt"))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_verify_detects_edit_after_read() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "hello world\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(literal(&file, "world", "rust"));
+    let outcome = set.apply().unwrap();
+    assert_eq!(outcome.stamps[&file].as_ref().unwrap().len, 12);
+
+    fs::write(&file, "hello world, edited\n").unwrap();
+    let err = outcome.verify().unwrap_err();
+    assert!(matches!(err, PatchError::FileChanged { file: f } if f == file));
+}
+
+#[test]
+fn test_touched_file_with_same_contents_still_matches() {
+    let dir = TempDir::new().unwrap();
+    let file = path(&dir, "a.txt");
+    fs::write(&file, "hello world\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(literal(&file, "world", "rust"));
+    let outcome = set.apply().unwrap();
+
+    let stamped = outcome.stamps[&file].as_ref().unwrap().modified.unwrap();
+    let handle = File::options().write(true).open(&file).unwrap();
+    handle
+        .set_modified(stamped + Duration::from_secs(60))
+        .unwrap();
+    outcome.verify().unwrap();
+
+    // Same length, different contents and a new modification time
+    fs::write(&file, "hello WORLD\n").unwrap();
+    handle
+        .set_modified(stamped + Duration::from_secs(120))
+        .unwrap();
+    assert!(outcome.verify().is_err());
+}
+
+#[test]
+fn test_verify_detects_created_and_deleted_files() {
+    let dir = TempDir::new().unwrap();
+    let created = path(&dir, "new.txt");
+    let deleted = path(&dir, "old.txt");
+    fs::write(&deleted, "old\n").unwrap();
+
+    let mut set = PatchSet::new();
+    set.add(
+        Patch::new(created.clone(), Snippet::All, "new\n")
+            .with_operation(Operation::CreateFile { overwrite: false }),
+    );
+    let outcome = set.apply().unwrap();
+    fs::write(&created, "made elsewhere\n").unwrap();
+    assert!(matches!(
+        outcome.verify(),
+        Err(PatchError::FileChanged { .. })
+    ));
+
+    let mut set = PatchSet::new();
+    set.add(literal(&deleted, "old", "older"));
+    let outcome = set.apply().unwrap();
+    fs::remove_file(&deleted).unwrap();
+    assert!(matches!(
+        outcome.verify(),
+        Err(PatchError::FileChanged { .. })
+    ));
+}
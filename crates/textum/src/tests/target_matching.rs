@@ -138,3 +138,32 @@ fn test_resolve_pattern_not_found() {
     let target = Target::pattern(r"\d+").unwrap();
     assert!(matches!(target.resolve(&rope), Err(TargetError::NotFound)));
 }
+
+#[test]
+fn test_resolve_all_literal_multibyte() {
+    let rope = Rope::from_str("café café");
+    let target = Target::Literal("café".to_string());
+    assert_eq!(target.resolve_all(&rope).unwrap(), vec![(0, 4), (5, 9)]);
+}
+
+#[test]
+fn test_resolve_all_literal_absent() {
+    let rope = Rope::from_str("hello");
+    let target = Target::Literal("world".to_string());
+    assert!(target.resolve_all(&rope).unwrap().is_empty());
+}
+
+#[test]
+fn test_resolve_all_line_is_single() {
+    let rope = Rope::from_str("a\nb\n");
+    assert_eq!(Target::Line(1).resolve_all(&rope).unwrap(), vec![(2, 4)]);
+    assert!(Target::Line(9).resolve_all(&rope).unwrap().is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_resolve_all_pattern() {
+    let rope = Rope::from_str("é1 é22");
+    let target = Target::pattern(r"\d+").unwrap();
+    assert_eq!(target.resolve_all(&rope).unwrap(), vec![(1, 2), (4, 6)]);
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::composer::test_helpers::path;
+use tempfile::TempDir;
+
+fn leftovers(dir: &TempDir) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.contains(".textum-"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_commit_writes_creates_and_deletes() {
+    let dir = TempDir::new().unwrap();
+    let edited = path(&dir, "edited.txt");
+    let removed = path(&dir, "removed.txt");
+    let created = path(&dir, "nested/deeper/created.txt");
+    fs::write(&edited, "before\n").unwrap();
+    fs::write(&removed, "gone\n").unwrap();
+
+    let changes = HashMap::from([
+        (edited.clone(), FileChange::Write("after\n".to_string())),
+        (removed.clone(), FileChange::Delete),
+        (created.clone(), FileChange::Write("new\n".to_string())),
+    ]);
+    commit(&changes).unwrap();
+
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "after\n");
+    assert!(!Path::new(&removed).exists());
+    assert_eq!(fs::read_to_string(&created).unwrap(), "new\n");
+    assert!(leftovers(&dir).is_empty());
+}
+
+#[test]
+fn test_failed_rename_restores_files_already_replaced() {
+    let dir = TempDir::new().unwrap();
+    let first = path(&dir, "a.txt");
+    let created = path(&dir, "b.txt");
+    // A non-empty directory cannot be replaced by a file, so this write fails after the
+    // writes ordered before it are in place
+    let blocked = path(&dir, "c");
+    fs::write(&first, "original\n").unwrap();
+    fs::create_dir(&blocked).unwrap();
+    fs::write(path(&dir, "c/inner.txt"), "x").unwrap();
+
+    let changes = HashMap::from([
+        (first.clone(), FileChange::Write("patched\n".to_string())),
+        (created.clone(), FileChange::Write("new\n".to_string())),
+        (blocked.clone(), FileChange::Write("file\n".to_string())),
+    ]);
+    let err = commit(&changes).unwrap_err();
+
+    assert!(matches!(&err, PatchError::WriteFailed { file, .. } if *file == blocked));
+    assert_eq!(fs::read_to_string(&first).unwrap(), "original\n");
+    assert!(!Path::new(&created).exists());
+    assert!(Path::new(&blocked).is_dir());
+    assert!(leftovers(&dir).is_empty());
+}
+
+#[test]
+fn test_failed_delete_restores_writes_and_new_directories() {
+    let dir = TempDir::new().unwrap();
+    let edited = path(&dir, "edited.txt");
+    let created = path(&dir, "new/dir/created.txt");
+    let missing = path(&dir, "missing.txt");
+    fs::write(&edited, "original\n").unwrap();
+
+    let changes = HashMap::from([
+        (edited.clone(), FileChange::Write("patched\n".to_string())),
+        (created.clone(), FileChange::Write("new\n".to_string())),
+        (missing.clone(), FileChange::Delete),
+    ]);
+    let err = commit(&changes).unwrap_err();
+
+    assert!(matches!(&err, PatchError::WriteFailed { file, .. } if *file == missing));
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "original\n");
+    assert!(!dir.path().join("new").exists());
+    assert!(leftovers(&dir).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commit_keeps_file_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let script = path(&dir, "run.sh");
+    fs::write(&script, "echo old\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let changes = HashMap::from([(script.clone(), FileChange::Write("echo new\n".to_string()))]);
+    commit(&changes).unwrap();
+
+    assert_eq!(fs::read_to_string(&script).unwrap(), "echo new\n");
+    let mode = fs::metadata(&script).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commit_writes_through_symlink() {
+    let dir = TempDir::new().unwrap();
+    let real = path(&dir, "real.txt");
+    let link = path(&dir, "link.txt");
+    fs::write(&real, "old\n").unwrap();
+    std::os::unix::fs::symlink(&real, &link).unwrap();
+
+    let changes = HashMap::from([(link.clone(), FileChange::Write("new\n".to_string()))]);
+    commit(&changes).unwrap();
+
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(fs::read_to_string(&real).unwrap(), "new\n");
+    assert!(leftovers(&dir).is_empty());
+}
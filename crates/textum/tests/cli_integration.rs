@@ -142,6 +142,253 @@ mod cli_integration {
         assert_eq!(result, "version=2.0.0");
     }
 
+    #[test]
+    fn cli_applies_whole_word_literal_patch() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("ident.rs");
+        fs::write(&source_file, "let valid = idx + id;").unwrap();
+
+        let patch_json = format!(
+            r#"[{{
+                "file": "{}",
+                "snippet": {{
+                    "At": {{
+                        "target": {{
+                            "LiteralWith": {{
+                                "needle": "id",
+                                "options": {{"word_boundary": true}}
+                            }}
+                        }},
+                        "mode": "Include"
+                    }}
+                }},
+                "replacement": "key"
+            }}]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patch_json)
+            .assert()
+            .success();
+
+        let result = fs::read_to_string(&source_file).unwrap();
+        assert_eq!(result, "let valid = idx + key;");
+    }
+
+    #[test]
+    fn cli_creates_deletes_and_renames_files() {
+        let temp = TempDir::new().unwrap();
+
+        let created = temp.path().join("src").join("created.rs");
+        let deleted = temp.path().join("deleted.rs");
+        let from = temp.path().join("from.rs");
+        let to = temp.path().join("to.rs");
+        fs::write(&deleted, "gone\n").unwrap();
+        fs::write(&from, "moved\n").unwrap();
+
+        let patch_json = format!(
+            r#"[
+                {{"file": "{}", "operation": {{"CreateFile": {{}}}}, "replacement": "new\n"}},
+                {{"file": "{}", "operation": "DeleteFile"}},
+                {{"file": "{}", "operation": {{"RenameFile": {{"to": "{}"}}}}}}
+            ]"#,
+            created.display(),
+            deleted.display(),
+            from.display(),
+            to.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patch_json)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Deleted:"));
+
+        assert_eq!(fs::read_to_string(&created).unwrap(), "new\n");
+        assert!(!deleted.exists());
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "moved\n");
+    }
+
+    #[test]
+    fn cli_error_cites_patch_id() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("ids.txt");
+        fs::write(&source_file, "hello\n").unwrap();
+
+        let patch_json = format!(
+            r#"[{{
+                "id": "greet",
+                "file": "{}",
+                "snippet": {{"At": {{"target": {{"Literal": "goodbye"}}, "mode": "Include"}}}},
+                "replacement": "hi"
+            }}]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patch_json)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Patch 0 (\"greet\")"));
+    }
+
+    #[test]
+    fn cli_sequential_mode() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("seq.txt");
+        fs::write(&source_file, "old\n").unwrap();
+
+        let patch = |needle: &str, replacement: &str| {
+            format!(
+                r#"{{
+                    "file": "{}",
+                    "snippet": {{"At": {{"target": {{"Literal": "{needle}"}}, "mode": "Include"}}}},
+                    "replacement": "{replacement}"
+                }}"#,
+                source_file.display()
+            )
+        };
+        let patches = format!("[{}, {}]", patch("old", "new"), patch("new", "newer"));
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patches.clone())
+            .assert()
+            .failure();
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "old\n");
+
+        cargo_bin_cmd!("textum")
+            .arg("--sequential")
+            .write_stdin(patches.clone())
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "newer\n");
+
+        // The mode can also be set in the JSON document
+        fs::write(&source_file, "old\n").unwrap();
+        cargo_bin_cmd!("textum")
+            .write_stdin(format!(r#"{{"mode": "Sequential", "patches": {patches}}}"#))
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "newer\n");
+    }
+
+    #[test]
+    fn cli_applies_operation_patches() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("ops.rs");
+        fs::write(&source_file, "let total = price * qty;\n").unwrap();
+
+        let patch_json = format!(
+            r#"[
+                {{
+                    "file": "{0}",
+                    "snippet": {{"At": {{"target": {{"Literal": "price * qty"}}, "mode": "Include"}}}},
+                    "operation": {{"Wrap": {{"prefix": "(", "suffix": ")"}}}}
+                }},
+                {{
+                    "file": "{0}",
+                    "snippet": "All",
+                    "replacement": "// generated\n",
+                    "operation": "Prepend"
+                }}
+            ]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patch_json)
+            .assert()
+            .success();
+
+        let result = fs::read_to_string(&source_file).unwrap();
+        assert_eq!(result, "// generated\nlet total = (price * qty);\n");
+    }
+
+    #[test]
+    fn cli_inverse_file_undoes_run() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("undo.txt");
+        fs::write(&source_file, "alpha\nbeta\ngamma\n").unwrap();
+        let inverse_file = temp.path().join("inverse.json");
+
+        let patch_json = format!(
+            r#"[
+                {{
+                    "file": "{0}",
+                    "snippet": {{"At": {{"target": {{"Literal": "beta"}}, "mode": "Include"}}}},
+                    "replacement": "BETA"
+                }},
+                {{
+                    "file": "{0}",
+                    "snippet": {{"At": {{"target": {{"Line": 0}}, "mode": "Include"}}}},
+                    "operation": "Delete"
+                }}
+            ]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .arg("--inverse")
+            .arg(inverse_file.to_str().unwrap())
+            .write_stdin(patch_json)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Wrote inverse:"));
+
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "BETA\ngamma\n");
+
+        cargo_bin_cmd!("textum")
+            .arg(inverse_file.to_str().unwrap())
+            .assert()
+            .success();
+
+        assert_eq!(
+            fs::read_to_string(&source_file).unwrap(),
+            "alpha\nbeta\ngamma\n"
+        );
+    }
+
+    #[test]
+    fn cli_normalises_line_endings() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("crlf.txt");
+        fs::write(&source_file, "one\r\ntwo\r\n").unwrap();
+
+        let patch_json = format!(
+            r#"[{{
+                "file": "{}",
+                "snippet": {{"At": {{"target": {{"Literal": "two"}}, "mode": "Include"}}}},
+                "replacement": "two\nthree"
+            }}]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patch_json.clone())
+            .assert()
+            .success();
+        assert_eq!(
+            fs::read_to_string(&source_file).unwrap(),
+            "one\r\ntwo\r\nthree\r\n"
+        );
+
+        cargo_bin_cmd!("textum")
+            .arg("--line-endings")
+            .arg("bogus")
+            .write_stdin(patch_json)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid line ending"));
+    }
+
     #[test]
     fn cli_applies_deletion_patch() {
         let temp = TempDir::new().unwrap();
@@ -289,6 +536,177 @@ mod cli_integration {
             .stderr(predicate::str::contains("Overlapping"));
     }
 
+    #[test]
+    fn cli_overlap_policy() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("overlap.txt");
+        fs::write(&source_file, "abcdef").unwrap();
+
+        let patch_json = format!(
+            r#"[
+                {{"file": "{0}", "snippet": {{"At": {{"target": {{"Literal": "bcd"}}, "mode": "Include"}}}}, "replacement": "XXX"}},
+                {{"file": "{0}", "snippet": {{"At": {{"target": {{"Literal": "def"}}, "mode": "Include"}}}}, "replacement": "YYY"}}
+            ]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .args(["--overlap", "last-wins"])
+            .write_stdin(patch_json)
+            .assert()
+            .success();
+
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "abcYYY");
+    }
+
+    #[test]
+    fn cli_prints_report() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("report.txt");
+        fs::write(&source_file, "hello world\n").unwrap();
+
+        let patch_json = format!(
+            r#"[{{
+                "id": "greet",
+                "file": "{}",
+                "snippet": {{"At": {{"target": {{"Literal": "world"}}, "mode": "Include"}}}},
+                "replacement": "rust"
+            }}]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .args(["--dry-run", "--report"])
+            .write_stdin(patch_json)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""id":"greet""#))
+            .stdout(predicate::str::contains(r#""status":"Applied""#))
+            .stdout(predicate::str::contains(r#""inserted":"rust""#));
+
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello world\n");
+    }
+
+    #[test]
+    fn cli_report_stays_parseable_with_verbose_dry_run() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("report.txt");
+        fs::write(&source_file, "hello world\n").unwrap();
+
+        let patch_json = format!(
+            r#"[{{
+                "file": "{}",
+                "snippet": {{"At": {{"target": {{"Literal": "world"}}, "mode": "Include"}}}},
+                "replacement": "rust"
+            }}]"#,
+            source_file.display()
+        );
+
+        let output = cargo_bin_cmd!("textum")
+            .args(["-n", "-v", "--report"])
+            .write_stdin(patch_json)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let report: textum::ApplyReport = facet_json::from_str(stdout.trim()).unwrap();
+        assert_eq!(report.patches.len(), 1);
+        assert_eq!(report.patches[0].inserted, "rust");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("hello rust"));
+    }
+
+    #[test]
+    fn cli_atomicity_patch_applies_what_resolves() {
+        let temp = TempDir::new().unwrap();
+
+        let source_file = temp.path().join("partial.txt");
+        fs::write(&source_file, "hello world\n").unwrap();
+
+        let patch = |needle: &str, replacement: &str| {
+            format!(
+                r#"{{
+                    "file": "{}",
+                    "snippet": {{"At": {{"target": {{"Literal": "{needle}"}}, "mode": "Include"}}}},
+                    "replacement": "{replacement}"
+                }}"#,
+                source_file.display()
+            )
+        };
+        let patches = format!("[{}, {}]", patch("world", "rust"), patch("absent", "x"));
+
+        cargo_bin_cmd!("textum")
+            .write_stdin(patches.clone())
+            .assert()
+            .failure();
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello world\n");
+
+        cargo_bin_cmd!("textum")
+            .args(["--atomicity", "patch"])
+            .write_stdin(patches)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Patched:"))
+            .stderr(predicate::str::contains("Patch 1 in"))
+            .stderr(predicate::str::contains("1 patch(es) failed"));
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello rust\n");
+    }
+
+    #[test]
+    fn cli_undo_restores_journaled_run() {
+        let temp = TempDir::new().unwrap();
+        let journal = temp.path().join("journal");
+
+        let source_file = temp.path().join("undo.txt");
+        fs::write(&source_file, "hello world\n").unwrap();
+
+        let patches = format!(
+            r#"[{{
+                "file": "{}",
+                "snippet": {{"At": {{"target": {{"Literal": "world"}}, "mode": "Include"}}}},
+                "replacement": "rust"
+            }}]"#,
+            source_file.display()
+        );
+
+        cargo_bin_cmd!("textum")
+            .args(["--backup", "journal", "--journal"])
+            .arg(&journal)
+            .write_stdin(patches)
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello rust\n");
+
+        fs::write(&source_file, "hello edited\n").unwrap();
+        cargo_bin_cmd!("textum")
+            .args(["undo", "--journal"])
+            .arg(&journal)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("File changed"));
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello edited\n");
+
+        fs::write(&source_file, "hello rust\n").unwrap();
+        cargo_bin_cmd!("textum")
+            .args(["undo", "--journal"])
+            .arg(&journal)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Restored:"));
+        assert_eq!(fs::read_to_string(&source_file).unwrap(), "hello world\n");
+
+        cargo_bin_cmd!("textum")
+            .args(["undo", "--journal"])
+            .arg(&journal)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No runs to undo"));
+    }
+
     #[test]
     fn cli_fails_on_invalid_json() {
         cargo_bin_cmd!("textum")